@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Affinity;
+
+//How an affinity deals and takes damage, affinities that are not listed are multiplied by 1
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Element {
+    pub id: String,
+    pub name: String,
+    //multiplies damage dealt to the listed affinities
+    #[serde(default)]
+    pub attack: HashMap<String, f32>,
+    //multiplies damage taken from the listed affinities
+    #[serde(default)]
+    pub resist: HashMap<String, f32>,
+}
+
+//Every affinity defined in affinities.toml, keyed by id
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct AffinityTable {
+    elements: HashMap<String, Element>,
+}
+
+impl AffinityTable {
+    pub fn new(elements: Vec<Element>) -> Self {
+        Self {
+            elements: elements.into_iter().map(|element| (element.id.clone(), element)).collect(),
+        }
+    }
+
+    pub fn contains(&self, affinity: &Affinity) -> bool {
+        self.elements.contains_key(&affinity.0)
+    }
+
+    /**
+     * Multiplier of damage dealt by one affinity to another
+     * attacker: &Affinity - The affinity of the damage
+     * defender: &Affinity - The affinity of whatever is hit
+     * returns: f32 - The attack multiplier of the attacker times the resistance of the defender
+     */
+    pub fn multiplier(&self, attacker: &Affinity, defender: &Affinity) -> f32 {
+        let attack = self.elements.get(&attacker.0)
+            .and_then(|element| element.attack.get(&defender.0))
+            .cloned()
+            .unwrap_or(1.0);
+        let resist = self.elements.get(&defender.0)
+            .and_then(|element| element.resist.get(&attacker.0))
+            .cloned()
+            .unwrap_or(1.0);
+        attack * resist
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(id: &str, attack: &[(&str, f32)], resist: &[(&str, f32)]) -> Element {
+        Element {
+            id: id.to_string(),
+            name: id.to_string(),
+            attack: attack.iter().map(|(id, value)| (id.to_string(), *value)).collect(),
+            resist: resist.iter().map(|(id, value)| (id.to_string(), *value)).collect(),
+        }
+    }
+
+    fn affinity(id: &str) -> Affinity {
+        Affinity(id.to_string())
+    }
+
+    #[test]
+    fn multiplier_combines_attack_and_resist() {
+        let table = AffinityTable::new(vec![
+            element("Fire", &[("Earth", 2.0)], &[("Water", 1.5)]),
+            element("Earth", &[], &[("Fire", 0.5)]),
+        ]);
+        assert_eq!(table.multiplier(&affinity("Fire"), &affinity("Earth")), 1.0);
+        assert_eq!(table.multiplier(&affinity("Water"), &affinity("Fire")), 1.5);
+    }
+
+    #[test]
+    fn multiplier_defaults_to_one() {
+        let table = AffinityTable::new(vec![element("Fire", &[("Earth", 2.0)], &[])]);
+        assert_eq!(table.multiplier(&affinity("Fire"), &affinity("Water")), 1.0);
+        assert_eq!(table.multiplier(&affinity("Dark"), &affinity("Light")), 1.0);
+        assert_eq!(table.multiplier(&Affinity::none(), &Affinity::none()), 1.0);
+    }
+}
\ No newline at end of file
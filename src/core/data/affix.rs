@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Stats, item::Kind};
+
+//Where the name of an affix goes around the name of the item
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum Position {
+    Prefix,
+    Suffix,
+}
+
+//Extra stats that can roll on an item when it drops
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Affix {
+    pub id: String,
+    pub name: String,
+    pub position: Position,
+    //kinds of items the affix can roll on
+    pub kinds: Vec<Kind>,
+    //chance from 0 to 1 the affix rolls
+    pub chance: f32,
+    //stats times a multiplier rolled between min and max
+    pub stats: Stats,
+    pub min: f32,
+    pub max: f32,
+}
\ No newline at end of file
@@ -24,7 +24,7 @@ impl Default for Character {
             name: "".to_string(),
             level: 0,
             experience: 0,
-            affinity: Affinity::None,
+            affinity: Affinity::none(),
             stats: Stats::default(),
             inventory: Inventory::default()
         }
@@ -7,4 +7,11 @@ pub struct Class {
     pub id: String,
     pub name: String,
     pub stats: Stats,
+    //multiplies the mana pool and how fast it comes back
+    #[serde(default = "default_mana")]
+    pub mana: f32,
+}
+
+fn default_mana() -> f32 {
+    1.0
 }
\ No newline at end of file
@@ -20,6 +20,23 @@ pub struct DungeonChunk {
 }
 
 impl DungeonChunk {
+    /**
+     * Finds the tile at a position of the chunk as it is drawn, after its rotations
+     * local: (usize, usize) - The column and row of the tile in the drawn chunk
+     * returns: Option<u16> - The tile, None when the position is outside of the matrix
+     */
+    pub fn tile(&self, local: (usize, usize)) -> Option<u16> {
+        let size = self.matrix.len();
+        let (mut x, mut y) = local;
+        //every rotation turns the matrix 90 degrees to the right, so undo them one at a time
+        for _ in 0..self.rotations % 4 {
+            let (column, row) = (y, size.checked_sub(1 + x)?);
+            x = column;
+            y = row;
+        }
+        self.matrix.get(y)?.get(x).cloned()
+    }
+
     /**
      * Rotates a dungeon that matches the dungeon's entries to the given entries
      * entries: u8 - The entries to rotate to
@@ -181,6 +198,11 @@ impl Dungeon {
         }
     }
 
+    //size of the dungeon in chunks
+    pub fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
     pub fn get_chunk(&self, location: (u32, u32)) -> Option<&DungeonChunk> {
         if location.0 >= self.size.0 || location.1 >= self.size.1 {
             error!("Attempted to get a chunk outside of the dungeon bounds");
@@ -189,6 +211,17 @@ impl Dungeon {
         self.chunks[location.0 as usize][location.1 as usize].as_ref()
     }
 
+    //tile at a position counted in tiles from the top left of the dungeon, None outside of any chunk
+    pub fn get_tile(&self, tile: (i32, i32)) -> Option<u16> {
+        if tile.0 < 0 || tile.1 < 0 {
+            return None;
+        }
+        let size = crate::core::toml_loader::CHUNK_SIZE as i32;
+        let (x, y) = ((tile.0 / size) as usize, (tile.1 / size) as usize);
+        let chunk = self.chunks.get(x)?.get(y)?.as_ref()?;
+        chunk.tile(((tile.0 % size) as usize, (tile.1 % size) as usize))
+    }
+
     pub fn rooms(self) -> Vec<(u32,u32)> {
         let mut rooms = Vec::new();
         for i in 0..self.size.0 {
@@ -203,6 +236,24 @@ impl Dungeon {
         rooms
     }
 
+    //chunks that have a location, along with the location
+    pub fn locations(&self) -> Vec<((u32, u32), &location::Location)> {
+        let mut locations = Vec::new();
+        for (x, column) in self.chunks.iter().enumerate() {
+            for (y, chunk) in column.iter().enumerate() {
+                if let Some(location) = chunk.as_ref().and_then(|chunk| chunk.spawn.as_ref()) {
+                    locations.push(((x as u32, y as u32), location));
+                }
+            }
+        }
+        locations
+    }
+
+    //location in a room chunk, if there is one
+    pub fn get_location(&self, pos: (u32, u32)) -> Option<&location::Location> {
+        self.get_chunk(pos)?.spawn.as_ref()
+    }
+
     pub fn add_location(&mut self, pos: (u32, u32), loc: location::Location) {
         if let Some(chunk) = self.chunks[pos.0 as usize][pos.1 as usize].as_mut() {
             chunk.spawn = Some(loc);
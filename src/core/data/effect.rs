@@ -9,6 +9,23 @@ pub enum Trigger {
     Proximity = 3,
 }
 
+//What happens when an effect is applied to an entity that already has it
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum Stacking {
+    //restarts the duration of the effect that is already there
+    Refresh,
+    //adds another instance, each running on its own
+    Stack,
+    //keeps the effect that is already there
+    Ignore,
+}
+
+impl Default for Stacking {
+    fn default() -> Self {
+        Self::Refresh
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Effect {
     pub id: String,
@@ -19,4 +36,12 @@ pub struct Effect {
     pub chance: f32,
     pub min: f32,
     pub max: f32,
+    #[serde(default)]
+    pub stacking: Stacking,
+    //health restored every interval, times the rolled multiplier
+    #[serde(default)]
+    pub heal: f32,
+    //damage dealt every interval, times the rolled multiplier
+    #[serde(default)]
+    pub damage: f32,
 }
\ No newline at end of file
@@ -1,7 +1,49 @@
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 
 use super::Stats;
 
+//Apparel slots an item can be worn in, matching the slots of character.toml
+pub const APPAREL_SLOTS: [&str; 6] = ["head", "body", "legs", "feet", "hands", "accessory"];
+
+//What an item is for, decides where it can be equipped
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    //worn in the apparel slot named by the item
+    Apparel,
+    //held in a hand
+    Weapon,
+    //held in a hand and used up
+    Consumable,
+    //coins, only ever carried
+    Currency,
+    Misc,
+}
+
+impl Default for Kind {
+    fn default() -> Self {
+        Self::Misc
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Default for Rarity {
+    fn default() -> Self {
+        Self::Common
+    }
+}
+
+fn default_stack() -> i32 {
+    1
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Item {
     pub id: String,
@@ -11,4 +53,58 @@ pub struct Item {
     pub effect: String,
     pub uses: i32,
     pub auto: bool,
+    #[serde(default)]
+    pub kind: Kind,
+    //apparel slot the item is worn in, empty for everything else
+    #[serde(default)]
+    pub slot: String,
+    //weapons that take up both hands
+    #[serde(default)]
+    pub two_handed: bool,
+    //most of the item a single inventory slot holds
+    #[serde(default = "default_stack")]
+    pub stack: i32,
+    #[serde(default)]
+    pub weight: f32,
+    #[serde(default)]
+    pub rarity: Rarity,
+    //coins the item is worth
+    #[serde(default)]
+    pub value: i32,
+    //seconds before a consumable of the same id can be used again
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+impl Item {
+    //every problem with the definition, empty when the item is fine
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match self.kind {
+            Kind::Apparel if !APPAREL_SLOTS.contains(&self.slot.as_str()) => problems.push(format!("apparel slot \"{}\" is not one of {:?}", self.slot, APPAREL_SLOTS)),
+            Kind::Apparel => {},
+            _ if !self.slot.is_empty() => problems.push(format!("only apparel has a slot, not {:?}", self.kind)),
+            _ => {},
+        }
+        if self.two_handed && self.kind != Kind::Weapon {
+            problems.push("only weapons can be two handed".to_string());
+        }
+        if self.stack < 1 {
+            problems.push("stack must be at least 1".to_string());
+        }
+        //items with an effect wear down on their own, so two of them are never the same
+        if self.stack > 1 && !self.effect.is_empty() {
+            problems.push("items with an effect cannot stack".to_string());
+        }
+        if self.weight < 0.0 {
+            problems.push("weight cannot be negative".to_string());
+        }
+        if self.cooldown < 0.0 {
+            problems.push("cooldown cannot be negative".to_string());
+        }
+        if self.value < 0 {
+            problems.push("value cannot be negative".to_string());
+        }
+        problems
+    }
 }
\ No newline at end of file
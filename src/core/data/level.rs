@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+//Experience curve and what every level gives, read from levels.toml
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Levels {
+    //experience needed to go from level 1 to 2
+    pub base: i32,
+    //how much more experience every following level needs
+    pub growth: f32,
+    //highest level that can be reached
+    pub max: i32,
+    //free stat points given every level
+    pub points: i32,
+    //fraction of the stats of the class added to the base stats every level
+    pub class_growth: f32,
+    //extra experience for every level of a mob above 1
+    pub mob_scale: f32,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels {
+            base: 100,
+            growth: 1.5,
+            max: 50,
+            points: 3,
+            class_growth: 0.1,
+            mob_scale: 0.25,
+        }
+    }
+}
+
+impl Levels {
+    //experience needed to go from a level to the next one
+    pub fn required(&self, level: i32) -> i32 {
+        (self.base as f32 * self.growth.powi((level - 1).max(0))).round() as i32
+    }
+
+    //experience given for a mob of a level
+    pub fn reward(&self, experience: i32, level: i32) -> i32 {
+        (experience as f32 * (1.0 + self.mob_scale * (level - 1).max(0) as f32)).round() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_grows_every_level() {
+        let levels = Levels::default();
+        assert_eq!(levels.required(1), 100);
+        assert_eq!(levels.required(2), 150);
+        assert_eq!(levels.required(3), 225);
+    }
+
+    #[test]
+    fn required_never_drops_below_the_first_level() {
+        let levels = Levels::default();
+        assert_eq!(levels.required(0), levels.required(1));
+        assert_eq!(levels.required(-5), levels.required(1));
+    }
+}
\ No newline at end of file
@@ -2,7 +2,13 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum Goal {
-    Hunt(Hunt)
+    Hunt(Hunt),
+    Collect(Collect),
+    Explore(Explore),
+    Escort(Escort),
+    Survive(Survive),
+    Deliver(Deliver),
+    Chain(Chain),
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -10,6 +16,8 @@ pub enum Trigger {
     None = -1,
     Death = 0, 
     Spawn = 1,
+    //only started by the chain it is a step of
+    Chain = 2,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -19,6 +27,77 @@ pub struct Hunt {
     pub target: String,
 }
 
+//Picking up a number of an item
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Collect {
+    pub number: i32,
+    pub count: i32,
+    pub item: String,
+}
+
+//Walking into the room of a location, or into a number of rooms when there is no location
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Explore {
+    pub number: i32,
+    pub count: i32,
+    #[serde(default)]
+    pub location: Option<String>,
+    //room chunks already counted
+    #[serde(default)]
+    pub visited: Vec<(u32, u32)>,
+}
+
+//Bringing a friendly mob that follows the player into the room of a location
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Escort {
+    pub target: String,
+    pub location: String,
+    #[serde(default)]
+    pub arrived: bool,
+}
+
+//Staying alive for a number of seconds, dying starts the count over
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Survive {
+    pub duration: f32,
+    #[serde(default)]
+    pub elapsed: f32,
+}
+
+//Handing a number of an item to a friendly mob
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Deliver {
+    pub number: i32,
+    pub count: i32,
+    pub item: String,
+    pub to: String,
+}
+
+//Finishing other missions one after the other, each is started once the one before it is done
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Chain {
+    pub missions: Vec<String>,
+    //index of the mission being worked on
+    #[serde(default)]
+    pub current: usize,
+}
+
+//Items given for finishing a mission
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct RewardItem {
+    pub id: String,
+    pub amount: i32,
+}
+
+//What finishing a mission gives
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Reward {
+    #[serde(default)]
+    pub experience: i32,
+    #[serde(default)]
+    pub items: Vec<RewardItem>,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Mission {
     pub id: String,
@@ -26,4 +105,6 @@ pub struct Mission {
     pub location: String,
     pub goal: Goal,
     pub trigger: Trigger,
+    #[serde(default)]
+    pub reward: Reward,
 }
\ No newline at end of file
@@ -1,14 +1,21 @@
+use std::ops::{Add, AddAssign};
+
 use serde::{Deserialize, Serialize};
 
 use super::toml_loader::Size;
+pub mod affinity;
+pub mod affix;
 pub mod character;
 pub mod class;
 pub mod effect;
 pub mod item;
+pub mod level;
 pub mod location;
 pub mod mission;
 pub mod mob;
+pub mod projectile;
 pub mod race;
+pub mod rune;
 pub mod dungeon;
 
 #[derive(Deserialize, Clone)]
@@ -18,7 +25,9 @@ pub enum Entity {
     Mob(mob::Mob),
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+//stats that are left out of a file are 0
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
 pub struct Stats {
     #[serde(rename = "str")]
     pub stg: i32,
@@ -46,6 +55,46 @@ impl Default for Stats {
     }
 }
 
+impl Stats {
+    //multiplies every stat, rounding to the nearest point
+    pub fn scaled(&self, multiplier: f32) -> Stats {
+        let scale = |value: i32| (value as f32 * multiplier).round() as i32;
+        Stats {
+            stg: scale(self.stg),
+            agi: scale(self.agi),
+            dex: scale(self.dex),
+            int: scale(self.int),
+            luk: scale(self.luk),
+            vit: scale(self.vit),
+            def: scale(self.def),
+            rng: scale(self.rng),
+        }
+    }
+}
+
+impl Add for Stats {
+    type Output = Stats;
+
+    fn add(self, other: Stats) -> Stats {
+        Stats {
+            stg: self.stg + other.stg,
+            agi: self.agi + other.agi,
+            dex: self.dex + other.dex,
+            int: self.int + other.int,
+            luk: self.luk + other.luk,
+            vit: self.vit + other.vit,
+            def: self.def + other.def,
+            rng: self.rng + other.rng,
+        }
+    }
+}
+
+impl AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        *self = self.clone() + other.clone();
+    }
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Slot {
     pub name: String,
@@ -72,14 +121,24 @@ impl Default for Inventory {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub enum Affinity {
-    None = -1,
-    Fire = 0,
-    Water = 1,
-    Earth = 2,
-    Air = 3,
-    Light = 4,
-    Dark = 5,
-    Lightning = 6,
+//Elemental affinity, the ids are defined in affinities.toml so mods can add their own
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Affinity(pub String);
+
+impl Affinity {
+    //the affinity of things without an element
+    pub fn none() -> Self {
+        Affinity("None".to_string())
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.0 == "None"
+    }
+}
+
+impl Default for Affinity {
+    fn default() -> Self {
+        Self::none()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+//Something shot through the air, read from projectiles.toml
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Projectile {
+    pub id: String,
+    pub name: String,
+    pub texture: String,
+    //chunks per second
+    pub speed: f32,
+    //seconds before it falls apart, shorter when the shooter has less range
+    pub lifetime: f32,
+    //width and height of its collider in texture pixels
+    pub size: f32,
+    //id of the effect applied to whatever it hits, empty for none
+    #[serde(default)]
+    pub effect: String,
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+//What a rune does when the spell it is in gets cast, written as "$name" in magic.toml
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum Action {
+    //moves the caster ahead along the aim
+    #[serde(rename = "$forward")]
+    Forward,
+    //shoots a bolt of magic along the aim
+    #[serde(rename = "$bolt")]
+    Bolt,
+    //sets how many tiles the following bolts fly before they dispel
+    #[serde(rename = "$range")]
+    Range,
+    //shields the caster from incoming magic
+    #[serde(rename = "$shield")]
+    Shield,
+}
+
+//A single step of a spell, read from magic.toml
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Rune {
+    pub id: String,
+    pub name: String,
+    pub texture: String,
+    pub description: String,
+    pub todo: Action,
+    //whether what the rune does shows up in the world
+    pub render_attribute: bool,
+    //mana the rune costs, -1 when it takes a number that is also its cost
+    pub drain: i32,
+    //id of what a bolt rune shoots
+    #[serde(default)]
+    pub projectile: String,
+}
+
+impl Rune {
+    pub fn takes_value(&self) -> bool {
+        self.drain < 0
+    }
+
+    //mana the rune costs with the number it was given
+    pub fn cost(&self, value: Option<i32>) -> i32 {
+        if self.takes_value() {
+            value.unwrap_or(0).max(0)
+        } else {
+            self.drain
+        }
+    }
+}
\ No newline at end of file
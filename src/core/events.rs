@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+
+use super::EntityId;
+
+//Something that happened in the world during a tick
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    //an entity came into the world or back to life, template is the data id it was made from
+    EntitySpawned { id: EntityId, template: Option<String> },
+    //killer is whoever dealt the last blow
+    EntityDied { id: EntityId, template: Option<String>, killer: Option<EntityId> },
+    DamageDealt { attacker: EntityId, target: EntityId, damage: i32, critical: bool },
+    //item is the data id of what was picked up
+    ItemPickedUp { holder: EntityId, item: String, amount: i32 },
+    //holder handed an amount of an item to an entity made from the template
+    ItemGiven { holder: EntityId, to: String, item: String, amount: i32 },
+    //an entity walked into a room chunk, along with the location id of the room
+    ChunkEntered { id: EntityId, chunk: (u32, u32), location: Option<String> },
+    LevelGained { id: EntityId, level: i32 },
+    //name is the display name of the mission id
+    MissionCompleted { id: String, name: String },
+}
+
+/**
+ * Events published during a tick, every system that runs after the publisher can read them until the tick ends
+ * Systems subscribe by picking the events they care about out of the bus, so they never have to know about each other
+ */
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    events: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn publish_all(&mut self, events: impl IntoIterator<Item = GameEvent>) {
+        self.events.extend(events);
+    }
+
+    /**
+     * Reads the events of this tick that a subscriber cares about
+     * pick: impl FnMut(&GameEvent) -> Option<T> - Turns the events the subscriber cares about into what it needs, None skips the event
+     * returns: impl Iterator<Item = T> - What was picked, in the order it was published
+     */
+    pub fn subscribe<'a, T>(&'a self, pick: impl FnMut(&'a GameEvent) -> Option<T> + 'a) -> impl Iterator<Item = T> + 'a {
+        self.events.iter().filter_map(pick)
+    }
+
+    //every event of this tick
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    //forgets the events of the tick that just ended
+    pub fn end_tick(&mut self) {
+        self.events.clear();
+    }
+}
\ No newline at end of file
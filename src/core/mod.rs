@@ -1,8 +1,10 @@
 use ggez::graphics;
+use serde::{Deserialize, Serialize};
 
 pub mod functions;
 pub mod data;
 pub mod toml_loader;
+pub mod events;
 
 pub mod logger {
     #[allow(unused_macros)]
@@ -63,13 +65,18 @@ pub mod logger {
     pub(crate) use alert;
 }
 
+//Handle to a live entity, ids are never reused within a scene
+pub type EntityId = u32;
+
 pub trait Event {
     fn update(&mut self, ctx: &mut ggez::Context);
     fn draw(&mut self, canvas: &mut graphics::Canvas);
     fn status(&self) -> bool;
+    //forwarded from the event handler, y is positive when scrolling up
+    fn mouse_wheel(&mut self, _x: f32, _y: f32) {}
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Direction {
     Down,
     Left,
@@ -81,4 +88,17 @@ impl Default for Direction {
     fn default() -> Self {
         Self::Down
     }
+}
+
+//What an entity is doing, used to pick an animation clip
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Motion {
+    Idle,
+    Walk,
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Self::Idle
+    }
 }
\ No newline at end of file
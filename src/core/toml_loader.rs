@@ -1,23 +1,29 @@
 use std::{path::PathBuf, collections::HashMap, env::{current_dir},io::Read};
 use crate::core::logger::debug;
 use super::{data::{
+    affinity::Element,
+    affix::Affix,
     character::Character,
     class::Class,
     effect::Effect,
     item::Item,
+    level::Levels,
     location::Location,
     mission::Mission,
     mob::Mob,
-    race::Race, self
-}, functions::{Vector4T}};
+    projectile::Projectile,
+    race::Race,
+    rune::Rune
+}, functions::{Vector4T, Vector2D}, Direction, Motion};
 use ggez::graphics;
 use image::GenericImage;
 use serde::{Serialize, Deserialize};
 
-#[allow(dead_code)]
-const TILE_SIZE: f32 = 32.0;
-const CHUNK_SIZE: f32 = 10.0;
+pub const TILE_SIZE: f32 = 32.0;
+pub const CHUNK_SIZE: f32 = 10.0;
 const RENDER_CHUNK_SIZE: f32 = 320.0;
+//MiB of VRAM chunk images may take up when the settings do not say
+const CHUNK_BUDGET: u32 = 64;
 
 //Position and size of a texture in a texture map
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -46,22 +52,64 @@ pub struct Texture {
     pub rect: Rect,
 }
 
+//A sequence of profiles played for one motion and direction
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Clip {
+    pub motion: Motion,
+    pub direction: Direction,
+    //indices of the profiles in the texture, left to right
+    pub frames: Vec<u32>,
+    //frames per second, 0 holds the first frame
+    pub fps: f32,
+    #[serde(rename = "loop")]
+    pub looping: bool,
+    //mirror the frames horizontally, used to get the left side from the right side
+    pub flip: bool,
+}
+
+//Animation layout shared by every texture listed in ids
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Animation {
+    pub ids: Vec<String>,
+    //size of a single profile
+    pub frame: Size,
+    pub clips: Vec<Clip>,
+    //where the texture sits on its wearer's frame, only used by equipment
+    #[serde(default)]
+    pub offset: Vector2D,
+}
+
+//Order equipment slots are drawn in when facing a direction, "base" is the wearer
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Layering {
+    pub direction: Direction,
+    pub slots: Vec<String>,
+    //shifts everything drawn in a slot, in pixels of the wearer's frame
+    #[serde(default)]
+    pub anchors: HashMap<String, Vector2D>,
+}
+
 //Lists position of textures in a texture map
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TextureMap {
     pub path: String,
     pub tiles: Vec<String>,
     pub textures: Vec<Texture>,
+    #[serde(default)]
+    pub animations: Vec<Animation>,
+    #[serde(default)]
+    pub layering: Vec<Layering>,
     #[serde(skip)]
     pub texture_buf: HashMap<String, image::ImageBuffer<image::Rgba<u8>, Vec<u8>>>,
     #[serde(skip)]
     pub tile_buf: Vec<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>>,
+    //the whole texture map, entities are drawn from it in a single batch
     #[serde(skip)]
-    pub chunk_buf: Vec<graphics::Image>,
+    pub atlas: Option<graphics::Image>,
 }
 
 impl TextureMap {
-    pub fn load_image(&mut self)  {
+    pub fn load_image(&mut self, ctx: &mut ggez::Context)  {
         #[cfg(debug_assertions)]
         let path = current_dir().unwrap().join("core").join("assets").join("textures").join(PathBuf::from(&self.path));
         #[cfg(not(debug_assertions))]
@@ -72,6 +120,7 @@ impl TextureMap {
         buf_reader.read_to_end(&mut buf).unwrap();
         let img = image::load_from_memory(&buf).unwrap();
         let mut image_buf = img.to_rgba8();
+        self.atlas = Some(graphics::Image::from_pixels(ctx, image_buf.as_raw(), graphics::ImageFormat::Rgba8UnormSrgb, image_buf.width(), image_buf.height()));
         for tile in &self.tiles {
             let tile_shape = self.textures.iter().find(|x| x.id == *tile).unwrap().rect.clone();
             let tile_buf = image_buf.sub_image(tile_shape.x as u32, tile_shape.y as u32, tile_shape.w as u32, tile_shape.h as u32).to_image();
@@ -85,6 +134,28 @@ impl TextureMap {
             self.texture_buf.insert(texture.id.clone(), texture_buf);
         }
     }
+
+    //get the animation layout for a texture id
+    pub fn get_animation(&self, id: &str) -> Option<&Animation> {
+        self.animations.iter().find(|x| x.ids.iter().any(|y| y == id))
+    }
+
+    pub fn get_rect(&self, id: &str) -> Option<&Rect> {
+        self.textures.iter().find(|x| x.id == id).map(|x| &x.rect)
+    }
+
+    //size of the texture map in pixels
+    pub fn get_atlas_size(&self) -> (f32, f32) {
+        if let Some(atlas) = self.atlas.as_ref() {
+            (atlas.width() as f32, atlas.height() as f32)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    pub fn get_layering(&self, direction: &Direction) -> Option<&Layering> {
+        self.layering.iter().find(|x| x.direction == *direction)
+    }
 }
 
 //Size of something in unsigned integer form
@@ -109,6 +180,41 @@ pub struct KeyMap {
     #[serde(rename = "use")]
     pub utilize: String,
     pub map: String,
+    //opens the character sheet
+    #[serde(default = "default_sheet")]
+    pub sheet: String,
+    //uses the consumables on the quickbar, one key for every slot
+    #[serde(default = "default_quickbar")]
+    pub quickbar: Vec<String>,
+    //opens the spellbook runes are chained in
+    #[serde(default = "default_spellbook")]
+    pub spellbook: String,
+    //casts the spell made in the spellbook
+    #[serde(default = "default_cast")]
+    pub cast: String,
+    //opens the quest log
+    #[serde(default = "default_journal")]
+    pub journal: String,
+}
+
+fn default_sheet() -> String {
+    "c".to_string()
+}
+
+fn default_spellbook() -> String {
+    "b".to_string()
+}
+
+fn default_cast() -> String {
+    "q".to_string()
+}
+
+fn default_journal() -> String {
+    "j".to_string()
+}
+
+fn default_quickbar() -> Vec<String> {
+    vec!["1".to_string(), "2".to_string(), "3".to_string(), "4".to_string()]
 }
 
 //Settings specific to user experience
@@ -121,12 +227,19 @@ pub struct Settings {
     pub fit: Size,
     pub keymap: KeyMap,
     pub scale: f32,
+    //MiB of VRAM the chunk images are allowed to take up
+    #[serde(default = "default_chunk_budget")]
+    pub chunk_budget: u32,
     #[serde(skip)]
     pub render_scale: f32,
     #[serde(skip)]
     pub render_size: f32,
 }
 
+fn default_chunk_budget() -> u32 {
+    CHUNK_BUDGET
+}
+
 impl Settings {
     pub fn set_render_scale(&mut self) {
         self.render_size = {
@@ -140,12 +253,6 @@ impl Settings {
         };
         self.render_scale = self.render_size / RENDER_CHUNK_SIZE;
     }
-    pub fn get_map_size(&self) -> (f32,f32) {
-        //render scale is the number of pixels in a chunk
-        let width = self.render_scale * self.size.w as f32;
-        let height = self.render_scale * self.size.h as f32;
-        (width, height)
-    }
 }
 
 //Overall configuration file
@@ -207,37 +314,11 @@ impl Configuration {
         }
     }
 
-    pub fn map_textures(&mut self) {
+    pub fn map_textures(&mut self, ctx: &mut ggez::Context) {
         for texture in &self.texture_map.textures {
             self.tex_map.insert(texture.id.clone(), texture.rect.clone());
         }
-        self.texture_map.load_image();
-    }
-
-    //pre-generate chunks
-    pub fn load_chunks(&mut self, ctx: &mut ggez::Context, dungeons: Vec<data::dungeon::DungeonChunk>) {
-        let size = (CHUNK_SIZE * TILE_SIZE) as u32;
-        for (i,chunk) in dungeons.iter().enumerate() {
-            let mut chunk_buf = image::ImageBuffer::new(size,size);
-            for (y, rows) in chunk.matrix.iter().enumerate() {
-                for (x, tile) in rows.iter().enumerate() {
-                    let tile_buf = self.texture_map.tile_buf[tile.clone() as usize].clone();
-                    let tile_buf = image::imageops::resize(&tile_buf, TILE_SIZE as u32, TILE_SIZE as u32, image::imageops::FilterType::Nearest);
-                    //overlay tile_buf onto chunk_buf
-                    image::imageops::overlay(&mut chunk_buf, &tile_buf, x as i64 * TILE_SIZE as i64, y as i64 * TILE_SIZE as i64);
-                }
-            }
-            #[cfg(debug_assertions)]
-            chunk_buf.save(format!("chunk_{}.png",i)).unwrap();
-            let mut writer:std::io::Cursor<Vec<_>> = std::io::Cursor::new(Vec::new());
-            chunk_buf.write_to(&mut writer, image::ImageOutputFormat::Png).unwrap();
-            self.texture_map.chunk_buf.push(graphics::Image::from_bytes(ctx,&writer.into_inner()).unwrap());
-        }
-    }
-
-    //get png of chunks
-    pub fn get_chunks(&self) -> Vec<graphics::Image> {
-        self.texture_map.chunk_buf.clone()
+        self.texture_map.load_image(ctx);
     }
 }
 
@@ -247,19 +328,27 @@ pub struct Dungeon {
     pub default_chunk: u16,
     pub net_weight: u16,
     pub chunks: Vec<super::data::dungeon::DungeonChunk>,
+    //tiles nothing can walk on
+    #[serde(default)]
+    pub solid: Vec<u16>,
 }
 
 #[derive(Deserialize)]
 pub enum TomlAsset {
     Configuration(Configuration),
+    Affinities(Vec<Element>),
+    Affixes(Vec<Affix>),
     Character(Character),
     Items(Vec<Item>),
+    Levels(Levels),
     Classes(Vec<Class>),
     Effects(Vec<Effect>),
     Locations(Vec<Location>),
     Missions(Vec<Mission>),
     Mobs(Vec<Mob>),
+    Projectiles(Vec<Projectile>),
     Races(Vec<Race>),
+    Runes(Vec<Rune>),
     Strings(Vec<String>),
     Dungeon(Dungeon),
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+use ggez::{graphics::{self, DrawParam, InstanceArray, Image}, glam};
+
+use super::scene::animation::Frame;
+
+//Draws every sprite sourced from the texture map in a single draw call
+pub struct SpriteBatch {
+    instances: InstanceArray,
+}
+
+impl SpriteBatch {
+    pub fn new(ctx: &mut ggez::Context, atlas: Image) -> Self {
+        Self {
+            instances: InstanceArray::new(ctx, atlas),
+        }
+    }
+
+    //keeps the allocated instances, so refilling the batch every frame does not allocate
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    //dest is the top left corner of the frame on screen
+    pub fn push(&mut self, frame: &Frame, dest: glam::Vec2, scale: f32) {
+        let param = DrawParam::default().src(frame.src).dest(dest);
+        if frame.flip {
+            //mirror around the right edge so the frame still starts at dest
+            self.instances.push(param.offset(glam::Vec2::new(1.0, 0.0)).scale(glam::Vec2::new(-scale, scale)));
+        } else {
+            self.instances.push(param.scale(glam::Vec2::new(scale, scale)));
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.instances.instances().len()
+    }
+
+    //returns the number of draw calls issued
+    pub fn draw(&self, canvas: &mut graphics::Canvas) -> usize {
+        if self.count() == 0 {
+            return 0;
+        }
+        canvas.draw(&self.instances, DrawParam::default());
+        1
+    }
+}
@@ -0,0 +1,103 @@
+use egui::{RichText, Color32};
+
+use crate::core::{data::Stats, toml_loader::CHUNK_SIZE};
+
+use super::scene::{stats::Sheet, components::{Health, Level}};
+
+//Names of the stats in the order they are shown
+pub const STATS: [&str; 8] = ["STR", "AGI", "DEX", "INT", "LUK", "VIT", "DEF", "RNG"];
+
+pub fn values(stats: &Stats) -> [i32; 8] {
+    [stats.stg, stats.agi, stats.dex, stats.int, stats.luk, stats.vit, stats.def, stats.rng]
+}
+
+//Character sheet toggled with the sheet key, shows where every stat comes from
+#[derive(Debug, Default)]
+pub struct CharacterSheet {
+    pub visible: bool,
+}
+
+impl CharacterSheet {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /**
+     * Shows the breakdown of the stats of an entity
+     * gui_ctx: &egui::Context - The context to show the window in
+     * name: &str - The name of the entity
+     * sheet: &Sheet - The effective stats of the entity
+     * health: Option<&Health> - The current health of the entity
+     * level: Option<(&Level, i32)> - The level of the entity and the experience needed for the next one, free points can be spent on the base stats
+     * text_size: f32 - The size of the text
+     * returns: Option<usize> - Index of the stat a point was spent on
+     */
+    pub fn show(&self, gui_ctx: &egui::Context, name: &str, sheet: &Sheet, health: Option<&Health>, level: Option<(&Level, i32)>, text_size: f32) -> Option<usize> {
+        if !self.visible {
+            return None;
+        }
+        let mut allocated = None;
+        let points = level.map_or(0, |(level, _)| level.points);
+        let text = |text: String| RichText::new(text).size(text_size);
+        egui::Window::new(RichText::new(name).size(text_size).color(Color32::DARK_GRAY))
+            .id(egui::Id::new("character_sheet"))
+            .resizable(false)
+            .collapsible(false)
+            .show(gui_ctx, |ui| {
+                if let Some((level, required)) = level {
+                    ui.label(text(format!("level {}  experience: {}/{}", level.level, level.experience, required)));
+                    if points > 0 {
+                        ui.label(text(format!("{} points to spend", points)).color(Color32::GOLD));
+                    }
+                    ui.separator();
+                }
+                egui::Grid::new("stats").striped(true).show(ui, |ui| {
+                    ui.label(text(String::new()));
+                    for stat in STATS {
+                        ui.label(text(stat.to_string()));
+                    }
+                    ui.end_row();
+                    for (source, stats) in &sheet.sources {
+                        ui.label(text(source.clone()));
+                        for value in values(stats) {
+                            //sources that give nothing are left blank so the rest stand out
+                            ui.label(text(if value == 0 { String::new() } else { format!("{:+}", value) }));
+                        }
+                        ui.end_row();
+                    }
+                    ui.label(text("total".to_string()).strong());
+                    for value in values(&sheet.total) {
+                        ui.label(text(value.to_string()).strong());
+                    }
+                    ui.end_row();
+                    //points go into the base stats, so they show up in the base row
+                    if points > 0 {
+                        ui.label(text(String::new()));
+                        for stat in 0..STATS.len() {
+                            if ui.button(text("+".to_string())).clicked() {
+                                allocated = Some(stat);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                let derived = &sheet.derived;
+                if let Some(health) = health {
+                    ui.label(text(format!("health: {}/{}", health.current, health.max)));
+                } else {
+                    ui.label(text(format!("health: {}", derived.health)));
+                }
+                ui.label(text(format!("mana: {}", derived.mana)));
+                ui.label(text(format!("move speed: {:.1} tiles/s", derived.move_speed * CHUNK_SIZE)));
+                ui.label(text(format!("attack speed: {:.2}/s", derived.attack_speed)));
+                ui.label(text(format!("cast speed: x{:.2}", derived.cast_speed)));
+                ui.label(text(format!("critical chance: {:.1}%", derived.crit * 100.0)));
+                ui.label(text(format!("loot chance: x{:.2}", derived.loot)));
+                ui.label(text(format!("damage blocked: {:.1}%", derived.mitigation * 100.0)));
+                ui.label(text(format!("perception: {:.1} tiles", derived.perception * CHUNK_SIZE)));
+            }
+        );
+        allocated
+    }
+}
\ No newline at end of file
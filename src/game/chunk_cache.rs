@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use ggez::graphics::{self, DrawParam, Image, InstanceArray};
+
+use crate::core::{data::dungeon::DungeonChunk, toml_loader::{CHUNK_SIZE, TILE_SIZE}, logger::error};
+
+//Most chunk images generated in a single update, spreads the work of large moves over a few frames
+const STREAM_PER_FRAME: usize = 4;
+
+//Bytes of VRAM used by one chunk image
+pub const CHUNK_BYTES: u32 = (CHUNK_SIZE * TILE_SIZE) as u32 * (CHUNK_SIZE * TILE_SIZE) as u32 * 4;
+
+//A generated chunk image and the chunks queued to be drawn with it
+struct Entry {
+    batch: InstanceArray,
+    last_used: u64,
+}
+
+//key of the image used longest ago, None when every image was used this tick
+fn least_recent(used: impl Iterator<Item = ((u16, u16), u64)>, tick: u64) -> Option<(u16, u16)> {
+    used.filter(|(_, last_used)| *last_used < tick)
+        .min_by_key(|(_, last_used)| *last_used)
+        .map(|(key, _)| key)
+}
+
+//Generates chunk images on demand and keeps the most recently used ones, keyed by id and rotation
+pub struct ChunkCache {
+    definitions: Vec<DungeonChunk>,
+    tiles: Vec<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>>,
+    entries: HashMap<(u16, u16), Entry>,
+    capacity: usize,
+    tick: u64,
+    generated: usize,
+}
+
+impl ChunkCache {
+    /**
+     * Creates an empty cache, no images are generated until they are requested
+     * definitions: Vec<DungeonChunk> - The chunk definitions, indexed by chunk id
+     * tiles: Vec<ImageBuffer> - The tile textures, indexed by the values of a chunk matrix
+     * capacity: usize - The most chunk images kept at once
+     */
+    pub fn new(definitions: Vec<DungeonChunk>, tiles: Vec<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>>, capacity: usize) -> Self {
+        //tiles are scaled once up front instead of every time a chunk is generated
+        let tiles = tiles.into_iter().map(|tile| {
+            if tile.width() == TILE_SIZE as u32 && tile.height() == TILE_SIZE as u32 {
+                tile
+            } else {
+                image::imageops::resize(&tile, TILE_SIZE as u32, TILE_SIZE as u32, image::imageops::FilterType::Nearest)
+            }
+        }).collect();
+        Self {
+            definitions,
+            tiles,
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            tick: 0,
+            generated: 0,
+        }
+    }
+
+    //starts a new frame, clearing the queued chunks but keeping their memory around
+    pub fn begin(&mut self) {
+        self.tick += 1;
+        self.generated = 0;
+        for entry in self.entries.values_mut() {
+            entry.batch.clear();
+        }
+    }
+
+    /**
+     * Makes sure the image of a chunk is cached, generating it when there is room this frame
+     * ctx: &mut ggez::Context - The context used to upload the image
+     * id: u16 - The id of the chunk definition
+     * rotations: u16 - The number of 90 degree rotations to the right
+     * returns: bool - Whether the chunk can be drawn this frame
+     */
+    pub fn request(&mut self, ctx: &mut ggez::Context, id: u16, rotations: u16) -> bool {
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&(id, rotations)) {
+            entry.last_used = tick;
+            return true;
+        }
+        if self.generated >= STREAM_PER_FRAME {
+            return false;
+        }
+        if self.entries.len() >= self.capacity && !self.evict() {
+            return false;
+        }
+        let image = if let Some(image) = self.generate(ctx, id, rotations) {
+            image
+        } else {
+            return false;
+        };
+        self.generated += 1;
+        self.entries.insert((id, rotations), Entry {
+            batch: InstanceArray::new(ctx, image),
+            last_used: tick,
+        });
+        true
+    }
+
+    //queues a chunk that was requested, does nothing for chunks that are not cached yet
+    pub fn push(&mut self, id: u16, rotations: u16, param: DrawParam) -> bool {
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&(id, rotations)) {
+            entry.last_used = tick;
+            entry.batch.push(param);
+            true
+        } else {
+            false
+        }
+    }
+
+    //number of chunks queued this frame
+    pub fn count(&self) -> usize {
+        self.entries.values().map(|entry| entry.batch.instances().len()).sum()
+    }
+
+    //number of chunk images held in VRAM
+    pub fn cached(&self) -> usize {
+        self.entries.len()
+    }
+
+    //returns the number of draw calls issued
+    pub fn draw(&self, canvas: &mut graphics::Canvas) -> usize {
+        let mut calls = 0;
+        for entry in self.entries.values() {
+            if !entry.batch.instances().is_empty() {
+                canvas.draw(&entry.batch, DrawParam::default());
+                calls += 1;
+            }
+        }
+        calls
+    }
+
+    //drops the least recently used image, chunks used this frame are never dropped
+    fn evict(&mut self) -> bool {
+        let oldest = least_recent(self.entries.iter().map(|(key, entry)| (*key, entry.last_used)), self.tick);
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+            true
+        } else {
+            false
+        }
+    }
+
+    //draws the tiles of a chunk straight into an rgba buffer and uploads it
+    fn generate(&self, ctx: &mut ggez::Context, id: u16, rotations: u16) -> Option<Image> {
+        let definition = if let Some(definition) = self.definitions.get(id as usize) {
+            definition
+        } else {
+            error!("Chunk {} is not defined", id);
+            return None;
+        };
+        let size = (CHUNK_SIZE * TILE_SIZE) as u32;
+        let mut chunk_buf = image::ImageBuffer::new(size, size);
+        for (y, rows) in definition.matrix.iter().enumerate() {
+            for (x, tile) in rows.iter().enumerate() {
+                if let Some(tile_buf) = self.tiles.get(*tile as usize) {
+                    image::imageops::replace(&mut chunk_buf, tile_buf, x as i64 * TILE_SIZE as i64, y as i64 * TILE_SIZE as i64);
+                } else {
+                    error!("Tile {} of chunk {} does not exist", tile, id);
+                }
+            }
+        }
+        //rotations are baked into the image, so chunks never have to be rotated when drawn
+        for _ in 0..rotations % 4 {
+            chunk_buf = image::imageops::rotate90(&chunk_buf);
+        }
+        Some(Image::from_pixels(ctx, chunk_buf.as_raw(), graphics::ImageFormat::Rgba8UnormSrgb, size, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_image_used_longest_ago() {
+        let used = [((0, 0), 5), ((1, 0), 2), ((2, 1), 4)];
+        assert_eq!(least_recent(used.into_iter(), 6), Some((1, 0)));
+    }
+
+    #[test]
+    fn keeps_images_used_this_tick() {
+        let used = [((0, 0), 6), ((1, 0), 6), ((2, 1), 3)];
+        assert_eq!(least_recent(used.into_iter(), 6), Some((2, 1)));
+        assert_eq!(least_recent(used.into_iter().take(2), 6), None);
+        assert_eq!(least_recent(std::iter::empty(), 6), None);
+    }
+}
\ No newline at end of file
@@ -2,10 +2,20 @@ use egui::{RichText, Button, Color32, Widget};
 use ggegui::{Gui};
 use ggez::{graphics::{self, DrawParam},glam};
 
-use crate::core::{toml_loader::Configuration,Event, logger::{debug, error}};
-use super::scene::Scene;
+use crate::core::{toml_loader::Configuration,Event, events::GameEvent, logger::{debug, error, alert}};
+use super::{input::Controls, character_sheet::CharacterSheet, inventory_screen::{InventoryScreen, Action}, spellbook::{Spellbook, Edit}, quest_log::QuestLog, scene::{Scene, camera::Camera, world::EntityId, systems, combat::Attack, magic::Fizzle}, batch::SpriteBatch, chunk_cache::{ChunkCache, CHUNK_BYTES}, profiler::Profiler};
 
 const TEXT_SIZE: f32 = 18.0;
+//How far the camera leans from the player towards the cursor
+const CURSOR_LEAD: f32 = 0.25;
+//Trauma added to the camera when the player lands a hit, doubled for critical hits
+const HIT_TRAUMA: f32 = 0.15;
+//Trauma added to the camera when the player is hit
+const HURT_TRAUMA: f32 = 0.4;
+//Seconds a notice stays on screen
+const NOTICE_TIME: f32 = 3.0;
+//Width of the mana and casting bars compared to the text
+const BAR_SCALE: f32 = 12.0;
 
 #[allow(dead_code)]
 const TILE_SIZE: f32 = 32.0;
@@ -16,28 +26,122 @@ pub struct Game {
     pub configuration: Box<Configuration>,
     pub gui: Gui,
     pub running: bool,
-    pub heartbeat: (u64,u64),
+    pub controls: Controls,
+    pub sheet: CharacterSheet,
+    pub inventory: InventoryScreen,
+    pub spellbook: Spellbook,
+    pub quest_log: QuestLog,
+    pub sprites: SpriteBatch,
+    pub chunks: ChunkCache,
+    pub profiler: Profiler,
+    //draw order of the entities, kept to avoid allocating every frame
+    pub order: Vec<EntityId>,
+    //text shown in the middle of the screen and the seconds it has left
+    pub notice: Option<(String, f32)>,
 }
 
 impl Game {
-    pub fn new(ctx: &mut ggez::Context, mut config: Box<Configuration>) -> Game {
+    /**
+     * Starts a game in a new dungeon
+     * ctx: &mut ggez::Context - The context of the window
+     * config: Box<Configuration> - The configuration the menu loaded
+     * load: bool - Whether the character comes from its save instead of character.toml
+     */
+    pub fn new(ctx: &mut ggez::Context, config: Box<Configuration>, load: bool) -> Game {
         let mut scene = Box::new(Scene::new(*(config.clone())));
-        config.load_chunks(ctx, scene.clone().map.unwrap().dungeon_list.clone());
-        scene.set_char_text(ctx, config.clone().texture_map.texture_buf);
-        let camera: (f32, f32) = (config.settings.get_map_size().0/2.0, config.settings.get_map_size().1/2.0);
-        scene.set_camera(camera);
+        if load && !scene.load() {
+            alert!("No character to load, starting a new one");
+        }
+        //start in the middle of the spawn chunk, which is in the middle of the dungeon
+        let center = ((config.settings.size.w / 2) as f32 + 0.5, (config.settings.size.h / 2) as f32 + 0.5);
+        scene.place_character(center);
+        let sprites = SpriteBatch::new(ctx, config.texture_map.atlas.clone().expect("Texture map was not loaded"));
+        //never hold fewer images than the chunks streamed around the camera at zoom 1, the camera zooms out only as far as the rest allows
+        let streamed = (config.settings.fit.w + 3) * (config.settings.fit.h + 3);
+        let streamed = streamed as usize;
+        //counted in u64, budgets of 4GiB and more do not fit in a u32 of bytes
+        let mut budget = (config.settings.chunk_budget as u64 * 1024 * 1024 / CHUNK_BYTES as u64) as usize;
+        if budget < streamed {
+            alert!("Chunk budget of {}MiB holds {} images, raising it to the {} streamed around the camera", config.settings.chunk_budget, budget, streamed);
+            budget = streamed;
+        }
+        scene.set_camera(Camera::new(center, &config.settings.size, budget));
+        let chunks = ChunkCache::new(scene.map.as_ref().unwrap().dungeon_list.clone(), config.texture_map.tile_buf.clone(), budget);
+        let controls = Controls::new(&config.settings.keymap);
         Game {
             data: scene,
             configuration: config,
             gui: Gui::new(ctx),
             running: true,
-            heartbeat: (0,0),
+            controls,
+            sheet: CharacterSheet::default(),
+            inventory: InventoryScreen::default(),
+            spellbook: Spellbook::default(),
+            quest_log: QuestLog::default(),
+            sprites,
+            chunks,
+            profiler: Profiler::new(),
+            order: Vec::new(),
+            notice: None,
+        }
+    }
+
+    //shakes the camera for hits and deaths the player was part of, and tells the player about missions and levels
+    fn react(&mut self) {
+        let player = self.data.player;
+        for event in self.data.bus.events() {
+            match event {
+                GameEvent::DamageDealt { attacker, target, damage, critical } => {
+                    if Some(*target) == player {
+                        self.data.camera.shake(HURT_TRAUMA);
+                    } else if Some(*attacker) == player {
+                        self.data.camera.shake(if *critical { HIT_TRAUMA * 2.0 } else { HIT_TRAUMA });
+                    }
+                    debug!("{} dealt {} damage to {}", attacker, damage, target);
+                },
+                GameEvent::EntityDied { id, .. } if Some(*id) == player => {
+                    self.data.camera.shake(1.0);
+                },
+                GameEvent::MissionCompleted { name, .. } => {
+                    self.notice = Some((format!("{} complete!", name), NOTICE_TIME));
+                },
+                GameEvent::LevelGained { id, level } if Some(*id) == player => {
+                    debug!("Player reached level {}", level);
+                    self.notice = Some((format!("Level {}!", level), NOTICE_TIME));
+                },
+                _ => {},
+            }
+        }
+    }
+
+    //generates the images of the chunks around the camera before they are drawn
+    fn stream_chunks(&mut self, ctx: &mut ggez::Context) {
+        self.chunks.begin();
+        let dungeon = &self.data.map.as_ref().unwrap().dungeon;
+        let ((start_x, start_y), (end_x, end_y)) = self.data.camera.visible_chunks(&self.configuration.settings, 1);
+        let (end_x, end_y) = (end_x.min(dungeon.get_size().0), end_y.min(dungeon.get_size().1));
+        //visible chunks come first so they win over the margin when streaming is throttled
+        let ((view_x, view_y), (view_end_x, view_end_y)) = self.data.camera.visible_chunks(&self.configuration.settings, 0);
+        for visible in [true, false] {
+            for x in start_x..end_x {
+                for y in start_y..end_y {
+                    let inside = x >= view_x && x < view_end_x && y >= view_y && y < view_end_y;
+                    if inside != visible {
+                        continue;
+                    }
+                    if let Some(chunk) = dungeon.get_chunk((x, y)) {
+                        self.chunks.request(ctx, chunk.id, chunk.rotations);
+                    }
+                }
+            }
         }
     }
 }
 
 impl Event for Game {
     fn update(&mut self, ctx: &mut ggez::Context) {
+        self.profiler.push_frame(ctx.time.delta());
+        self.profiler.begin();
         //go through scene and update all entities
         let gui_ctx = self.gui.ctx();
         //default as collapsed
@@ -61,10 +165,12 @@ impl Event for Game {
                             },
                             "Menu" => {
                                 debug!("Exit");
+                                self.data.save();
                                 self.running = false;
                             },
                             "Desktop" => {
                                 debug!("Quit");
+                                self.data.save();
                                 std::process::exit(0);
                             },
                             _ => {},
@@ -74,32 +180,89 @@ impl Event for Game {
             }
         );
 
-        if self.heartbeat.0 == 0 {
-            self.heartbeat.0 = ctx.time.fps() as u64;
-        }
-
-        //ensures movement is consistent across all computers
-        //check for key press every 1/100th of a second
-        // if every cycle is 1/144 aka 144 fps, then 1/100th of a second is 1.44 cycles
-        // we want to check for key press every 1/100th of a second
-        let cycle = 100.0 / self.heartbeat.0 as f64 * self.heartbeat.1 as f64;
-        
-        if cycle >= 100.0 {
-            if ctx.keyboard.is_key_pressed(ggez::input::keyboard::KeyCode::W) {
-                self.data.move_vert(-0.1);//refers to tile based movement
-            }else if ctx.keyboard.is_key_pressed(ggez::input::keyboard::KeyCode::S) {
-                self.data.move_vert(0.1);
+        let delta = ctx.time.delta().as_secs_f32();
+        self.data.update_effects(delta);
+        self.data.update_stats();
+        //moving by speed times delta keeps movement consistent across all computers
+        let speed = self.data.get_player_sheet().map_or(0.0, |sheet| sheet.derived.move_speed) * delta;
+        let up = Controls::pressed(&self.controls.up, ctx);
+        let down = Controls::pressed(&self.controls.down, ctx);
+        let left = Controls::pressed(&self.controls.left, ctx);
+        let right = Controls::pressed(&self.controls.right, ctx);
+        let moving = up || down || left || right;
+        if up {
+            self.data.move_vert(-speed);
+        } else if down {
+            self.data.move_vert(speed);
+        }
+        if left {
+            self.data.move_horiz(-speed);
+        } else if right {
+            self.data.move_horiz(speed);
+        }
+
+        let player = self.data.get_player_position();
+        let cursor = self.data.camera.screen_to_world(ctx.mouse.position().into(), &self.configuration.settings);
+        let following = self.configuration.settings.keymap.follow == "cursor";
+
+        //attacks go towards the cursor when following it, otherwise the way the player faces
+        let aim = if following {
+            (cursor.0 - player.0, cursor.1 - player.1)
+        } else {
+            self.data.get_player_facing()
+        };
+        self.data.update_spawns(delta);
+        self.data.update_combat(delta);
+        //clicking on the gui should not swing at whatever is behind it
+        let pointer_free = !gui_ctx.wants_pointer_input();
+        if pointer_free && Controls::pressed(&self.controls.attack, ctx) {
+            self.data.player_attack(Attack::Melee, aim);
+        } else if pointer_free && Controls::pressed(&self.controls.utilize, ctx) {
+            //holding a consumable drinks it instead of shooting
+            if !self.data.holds_consumable() {
+                self.data.player_attack(Attack::Ranged, aim);
+            } else if Controls::just_pressed(&self.controls.utilize, ctx) {
+                self.data.use_held();
+            }
+        }
+        if pointer_free && Controls::just_pressed(&self.controls.cast, ctx) {
+            match self.data.cast(aim) {
+                Ok(time) => debug!("Casting for {:.2}s", time),
+                Err(Fizzle::Mana(cost, mana)) => {
+                    self.notice = Some((format!("Not enough mana ({}/{})", mana, cost), NOTICE_TIME));
+                },
+                Err(fizzle) => debug!("Could not cast: {:?}", fizzle),
             }
-            if ctx.keyboard.is_key_pressed(ggez::input::keyboard::KeyCode::A) {
-                self.data.move_horiz(-0.1);
-            }else if ctx.keyboard.is_key_pressed(ggez::input::keyboard::KeyCode::D) {
-                self.data.move_horiz(0.1);
+        }
+        for index in 0..self.controls.quickbar.len() {
+            if Controls::just_pressed(&self.controls.quickbar[index], ctx) {
+                self.data.use_quick(index);
             }
-            self.heartbeat.1 = 0;
         }
-        self.heartbeat.1 += 1;
+        if Controls::just_pressed(&self.controls.interact, ctx) {
+            self.data.interact();
+        }
+        self.data.reap();
+        self.data.update_room();
+        self.data.update_missions(delta);
+        //everything that publishes has run, so the rest of the tick only reacts
+        self.react();
+        self.data.bus.end_tick();
 
-        let label_text = format!("FPS: {} Pos: {:?}", ctx.time.fps() as u64, self.data.camera);
+        let mut target = player;
+        if following {
+            target.0 += (cursor.0 - target.0) * CURSOR_LEAD;
+            target.1 += (cursor.1 - target.1) * CURSOR_LEAD;
+        }
+        self.data.camera.follow(target);
+        self.data.camera.update(delta, &self.configuration.settings);
+        self.stream_chunks(ctx);
+        self.data.dress(&self.configuration.texture_map);
+        self.data.animate(moving, delta);
+
+        let health = self.data.player.and_then(|player| self.data.world.healths.get(&player)).map_or((0, 0), |health| (health.current, health.max));
+        let level = self.data.player.and_then(|player| self.data.world.levels.get(&player)).map_or((0, 0), |level| (level.level, level.experience));
+        let label_text = format!("FPS: {} Pos: {:?} HP: {}/{} Lv: {} XP: {}/{}", ctx.time.fps() as u64, self.data.camera.position, health.0, health.1, level.0, level.1, self.data.levels.required(level.0));
         //draw fps in top right corner
         egui::Area::new("fps")
             .fixed_pos(egui::pos2(0.0, height - (TEXT_SIZE / 2.0 * self.configuration.settings.scale)))
@@ -107,26 +270,116 @@ impl Event for Game {
                 ui.label(RichText::new(label_text).size(TEXT_SIZE / 2.0 * self.configuration.settings.scale));
             }
         );
-        //Temporary movement, set to appropriate movement amount later
+        //mana sits in the top left, with the spell being cast under it
+        let mana = self.data.player.and_then(|player| self.data.world.manas.get(&player));
+        let casting = self.data.player.and_then(|player| self.data.world.castings.get(&player));
+        if let Some(mana) = mana {
+            let size = TEXT_SIZE / 2.0 * self.configuration.settings.scale;
+            egui::Area::new("mana")
+                .fixed_pos(egui::pos2(size, size))
+                .show(&gui_ctx, |ui| {
+                    let fill = if mana.max > 0 { mana.current / mana.max as f32 } else { 0.0 };
+                    ui.add(egui::ProgressBar::new(fill)
+                        .desired_width(size * BAR_SCALE)
+                        .fill(Color32::from_rgb(40, 80, 200))
+                        .text(RichText::new(format!("MP: {}/{}", mana.current as i32, mana.max)).size(size)));
+                    if let Some(casting) = casting {
+                        let done = if casting.total > 0.0 { 1.0 - casting.time / casting.total } else { 1.0 };
+                        ui.add(egui::ProgressBar::new(done)
+                            .desired_width(size * BAR_SCALE)
+                            .fill(Color32::from_rgb(140, 60, 200))
+                            .text(RichText::new("casting").size(size)));
+                    }
+                }
+            );
+        }
+        if let Some((text, time)) = self.notice.as_mut() {
+            *time -= delta;
+            egui::Area::new("notice")
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, -height / 4.0))
+                .show(&gui_ctx, |ui| {
+                    ui.label(RichText::new(text.as_str()).size(TEXT_SIZE * self.configuration.settings.scale).color(Color32::GOLD));
+                }
+            );
+            if *time <= 0.0 {
+                self.notice = None;
+            }
+        }
+        if Controls::just_pressed(&self.controls.sheet, ctx) {
+            self.sheet.toggle();
+        }
+        let mut allocated = None;
+        if let (Some(player), Some(sheet)) = (self.data.player, self.data.get_player_sheet()) {
+            let name = self.data.world.names.get(&player).map_or("", |name| name.as_str());
+            let level = self.data.world.levels.get(&player).map(|level| (level, self.data.levels.required(level.level)));
+            allocated = self.sheet.show(&gui_ctx, name, sheet, self.data.world.healths.get(&player), level, TEXT_SIZE / 2.0 * self.configuration.settings.scale);
+        }
+        if let Some(stat) = allocated {
+            self.data.allocate(stat);
+        }
+        if Controls::just_pressed(&self.controls.inventory, ctx) {
+            self.inventory.toggle();
+        }
+        let action = self.data.player.and_then(|player| {
+            let inventory = self.data.world.inventories.get(&player)?;
+            let cooldowns = self.data.world.effects.get(&player).map(|effects| &effects.item_cooldowns)?;
+            self.inventory.show(&gui_ctx, inventory, cooldowns, &self.configuration.texture_map, TEXT_SIZE / 2.0 * self.configuration.settings.scale)
+        });
+        match action {
+            Some(Action::Move(moved)) => {
+                if !self.data.move_item(&moved.from, &moved.to, moved.amount) {
+                    debug!("Could not move {:?} to {:?}", moved.from, moved.to);
+                }
+            },
+            Some(Action::Assign(index, item)) => self.data.assign_quick(index, item),
+            Some(Action::Repair(slot)) => {
+                self.data.repair(&slot);
+            },
+            None => {},
+        }
+
+        if Controls::just_pressed(&self.controls.spellbook, ctx) {
+            self.spellbook.toggle();
+        }
+        let edit = self.data.player
+            .and_then(|player| self.data.world.spells.get(&player))
+            .and_then(|spell| self.spellbook.show(&gui_ctx, &self.data.runes, spell, TEXT_SIZE / 2.0 * self.configuration.settings.scale));
+        match edit {
+            Some(Edit::Push(rune, value)) => {
+                if !self.data.push_rune(&rune, value) {
+                    debug!("Could not add {} to the spell", rune);
+                }
+            },
+            Some(Edit::Pop) => self.data.pop_rune(),
+            Some(Edit::Clear) => self.data.clear_spell(),
+            None => {},
+        }
+
+        if Controls::just_pressed(&self.controls.journal, ctx) {
+            self.quest_log.toggle();
+        }
+        self.quest_log.show(&gui_ctx, &self.data.journal, TEXT_SIZE / 2.0 * self.configuration.settings.scale);
+
+        if ctx.keyboard.is_key_just_pressed(ggez::input::keyboard::KeyCode::F3) {
+            self.profiler.toggle();
+        }
+        self.profiler.end_update();
+        self.profiler.show(&gui_ctx, TEXT_SIZE / 2.0 * self.configuration.settings.scale);
 
         self.gui.update(ctx);
     }
 
     //Do not clone when drawing, it will cause lag
     fn draw(&mut self, canvas: &mut graphics::Canvas) {
+        self.profiler.begin();
         //get all tiles in data.map.map
         //scale each tile by self.configuration.settings.fit
+        let ((start_x, start_y), (end_x, end_y)) = self.data.camera.visible_chunks(&self.configuration.settings, 0);
+        let scale = self.data.camera.scale(&self.configuration.settings);
         let val = self.data.map.as_ref().unwrap();
-        // //get chunk buffer
-        let chunk_buffer = self.configuration.get_chunks();
         let dungeon = &val.dungeon;
-        let camera = self.data.camera;
-        //camera is in chunks
-        //center chunk loading on camera
-        let start_x = camera.0 as u32 - (self.configuration.settings.fit.w / 2) as u32;
-        let start_y = camera.1 as u32 - (self.configuration.settings.fit.h / 2) as u32;
-        let end_x = start_x + self.configuration.settings.fit.w as u32 + 1; //add one as a buffer
-        let end_y = start_y + self.configuration.settings.fit.h as u32 + 1;
+        //batches are refilled every frame, clearing keeps their memory around
+        self.sprites.clear();
         //get all chunks in range
         for x in start_x..end_x {
             for y in start_y..end_y {
@@ -136,45 +389,22 @@ impl Event for Game {
                     error!("Chunk ({}, {}) not found", x, y);
                     continue;
                 };
-                canvas.draw(
-                    chunk_buffer.get(chunk.id as usize).unwrap(),
-                    DrawParam::default().scale(
-                        glam::Vec2::new(
-                            self.configuration.settings.render_scale,
-                            self.configuration.settings.render_scale
-                        )
-                    ).dest(
-                        glam::Vec2::new(
-                            (x - start_x) as f32 * self.configuration.settings.render_size - self.configuration.settings.render_size / 2.0,
-                            (y - start_y) as f32 * self.configuration.settings.render_size - self.configuration.settings.render_size / 2.0
-                        )
-                    ).rotation(
-                        //rotations refers to number of 90 degree rotations
-                        chunk.rotations as f32 * 90.0
-                    )
+                //chunks still being streamed in are skipped for this frame
+                self.chunks.push(
+                    chunk.id,
+                    chunk.rotations,
+                    DrawParam::default()
+                        .scale(glam::Vec2::new(scale, scale))
+                        .dest(self.data.camera.world_to_screen((x as f32, y as f32), &self.configuration.settings))
                 );
             }
         }
 
-        //draw player
-        let pos = (
-            self.configuration.settings.resolution.w / 2,
-            self.configuration.settings.resolution.h / 2
-        ); 
-        canvas.draw(
-            self.data.get_character_cln().get_texture(self.data.direction.clone()),
-            DrawParam::default().scale(
-                glam::Vec2::new(
-                    self.configuration.settings.render_scale,
-                    self.configuration.settings.render_scale
-                )
-            ).dest(
-                glam::Vec2::new(
-                    pos.0 as f32,
-                    pos.1 as f32
-                )
-            )
-        );
+        //draw the player and every other entity on screen
+        systems::draw(&self.data.world, &mut self.order, &mut self.sprites, &self.configuration.texture_map, &self.data.camera, &self.configuration.settings);
+
+        let draw_calls = self.chunks.draw(canvas) + self.sprites.draw(canvas);
+        self.profiler.end_draw(draw_calls, self.chunks.count() + self.sprites.count(), self.chunks.cached(), self.data.world.count());
 
         canvas.draw(&self.gui, DrawParam::default().dest(glam::Vec2::ZERO));
     }
@@ -182,4 +412,10 @@ impl Event for Game {
     fn status(&self) -> bool {
         self.running
     }
+
+    fn mouse_wheel(&mut self, _x: f32, y: f32) {
+        if y != 0.0 {
+            self.data.camera.zoom_by(y.signum(), &self.configuration.settings);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use ggez::input::{keyboard::KeyCode, mouse::MouseButton};
+
+use crate::core::logger::error;
+
+//A key or mouse button an action in the keymap is bound to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    //reads the names used in the keymap of mods.toml, such as "w", "escape" or "mouse1"
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let key = match name.as_str() {
+            "mouse1" => return Some(Binding::Mouse(MouseButton::Left)),
+            "mouse2" => return Some(Binding::Mouse(MouseButton::Right)),
+            "mouse3" => return Some(Binding::Mouse(MouseButton::Middle)),
+            "a" => KeyCode::A, "b" => KeyCode::B, "c" => KeyCode::C, "d" => KeyCode::D,
+            "e" => KeyCode::E, "f" => KeyCode::F, "g" => KeyCode::G, "h" => KeyCode::H,
+            "i" => KeyCode::I, "j" => KeyCode::J, "k" => KeyCode::K, "l" => KeyCode::L,
+            "m" => KeyCode::M, "n" => KeyCode::N, "o" => KeyCode::O, "p" => KeyCode::P,
+            "q" => KeyCode::Q, "r" => KeyCode::R, "s" => KeyCode::S, "t" => KeyCode::T,
+            "u" => KeyCode::U, "v" => KeyCode::V, "w" => KeyCode::W, "x" => KeyCode::X,
+            "y" => KeyCode::Y, "z" => KeyCode::Z,
+            "1" => KeyCode::Key1, "2" => KeyCode::Key2, "3" => KeyCode::Key3, "4" => KeyCode::Key4,
+            "5" => KeyCode::Key5, "6" => KeyCode::Key6, "7" => KeyCode::Key7, "8" => KeyCode::Key8,
+            "9" => KeyCode::Key9, "0" => KeyCode::Key0,
+            "escape" => KeyCode::Escape,
+            "space" => KeyCode::Space,
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Return,
+            "shift" => KeyCode::LShift,
+            "control" => KeyCode::LControl,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ => {
+                error!("Unknown binding {}", name);
+                return None;
+            }
+        };
+        Some(Binding::Key(key))
+    }
+
+    pub fn is_pressed(&self, ctx: &ggez::Context) -> bool {
+        match self {
+            Binding::Key(key) => ctx.keyboard.is_key_pressed(*key),
+            Binding::Mouse(button) => ctx.mouse.button_pressed(*button),
+        }
+    }
+
+    pub fn is_just_pressed(&self, ctx: &ggez::Context) -> bool {
+        match self {
+            Binding::Key(key) => ctx.keyboard.is_key_just_pressed(*key),
+            Binding::Mouse(button) => ctx.mouse.button_just_pressed(*button),
+        }
+    }
+}
+
+//The keymap with every action parsed, actions with an unknown binding are never pressed
+#[derive(Debug, Clone, Default)]
+pub struct Controls {
+    pub up: Option<Binding>,
+    pub down: Option<Binding>,
+    pub left: Option<Binding>,
+    pub right: Option<Binding>,
+    pub attack: Option<Binding>,
+    pub utilize: Option<Binding>,
+    pub sheet: Option<Binding>,
+    pub interact: Option<Binding>,
+    pub inventory: Option<Binding>,
+    pub quickbar: Vec<Option<Binding>>,
+    pub spellbook: Option<Binding>,
+    pub cast: Option<Binding>,
+    pub journal: Option<Binding>,
+}
+
+impl Controls {
+    pub fn new(keymap: &crate::core::toml_loader::KeyMap) -> Self {
+        Self {
+            up: Binding::parse(&keymap.up),
+            down: Binding::parse(&keymap.down),
+            left: Binding::parse(&keymap.left),
+            right: Binding::parse(&keymap.right),
+            attack: Binding::parse(&keymap.attack),
+            utilize: Binding::parse(&keymap.utilize),
+            sheet: Binding::parse(&keymap.sheet),
+            interact: Binding::parse(&keymap.interact),
+            inventory: Binding::parse(&keymap.inventory),
+            quickbar: keymap.quickbar.iter().map(|name| Binding::parse(name)).collect(),
+            spellbook: Binding::parse(&keymap.spellbook),
+            cast: Binding::parse(&keymap.cast),
+            journal: Binding::parse(&keymap.journal),
+        }
+    }
+
+    pub fn pressed(binding: &Option<Binding>, ctx: &ggez::Context) -> bool {
+        binding.map_or(false, |binding| binding.is_pressed(ctx))
+    }
+
+    pub fn just_pressed(binding: &Option<Binding>, ctx: &ggez::Context) -> bool {
+        binding.map_or(false, |binding| binding.is_just_pressed(ctx))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use egui::{RichText, Color32, ColorImage, TextureHandle, TextureOptions, TextureId, Sense, Rect, Stroke, Align2, FontId, Order, pos2, vec2};
+
+use crate::core::{toml_loader::TextureMap, data::item::{Rarity, Kind, APPAREL_SLOTS}};
+
+use super::{character_sheet::{STATS, values}, scene::{entity::{Inventory, Slot, SlotRef, Condition}, durability}};
+
+//Hands in the order they are shown
+const HOLDING: [&str; 2] = ["left", "right"];
+//Size of a cell compared to the text
+const CELL_SCALE: f32 = 2.5;
+
+fn rarity_color(rarity: &Rarity) -> Color32 {
+    match rarity {
+        Rarity::Common => Color32::WHITE,
+        Rarity::Uncommon => Color32::GREEN,
+        Rarity::Rare => Color32::LIGHT_BLUE,
+        Rarity::Epic => Color32::from_rgb(180, 90, 255),
+        Rarity::Legendary => Color32::GOLD,
+    }
+}
+
+fn condition_color(condition: &Condition) -> Color32 {
+    match condition {
+        Condition::Pristine => Color32::GREEN,
+        Condition::Worn => Color32::YELLOW,
+        Condition::Damaged => Color32::from_rgb(255, 140, 0),
+        Condition::Broken => Color32::RED,
+    }
+}
+
+//A move the player asked for, carried out by the scene
+#[derive(Debug, Clone)]
+pub struct Move {
+    pub from: SlotRef,
+    pub to: SlotRef,
+    //None moves the whole stack
+    pub amount: Option<i32>,
+}
+
+//Changes the player made in the inventory window or on the quickbar
+#[derive(Debug, Clone)]
+pub enum Action {
+    Move(Move),
+    //puts a consumable on a slot of the quickbar, None clears the slot
+    Assign(usize, Option<String>),
+    Repair(SlotRef),
+}
+
+//Inventory window toggled with the inventory key, items are dragged between the bag and the equipment
+#[derive(Default)]
+pub struct InventoryScreen {
+    pub visible: bool,
+    //item textures uploaded to the gui, keyed by texture id
+    icons: HashMap<String, TextureHandle>,
+    //slot being dragged from and whether only half of its stack is taken
+    dragging: Option<(SlotRef, bool)>,
+    //slot that was right clicked to be repaired
+    repairing: Option<SlotRef>,
+}
+
+impl InventoryScreen {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.dragging = None;
+    }
+
+    //uploads the first frame of a texture the first time it is shown
+    fn icon(&mut self, gui_ctx: &egui::Context, texture_map: &TextureMap, texture: &str) -> Option<TextureId> {
+        if let Some(icon) = self.icons.get(texture) {
+            return Some(icon.id());
+        }
+        let buffer = texture_map.texture_buf.get(texture)?;
+        //animated textures hold several frames side by side, only the first one is an icon
+        let (w, h) = texture_map.get_animation(texture)
+            .map_or((buffer.width(), buffer.height()), |animation| (animation.frame.w.min(buffer.width()), animation.frame.h.min(buffer.height())));
+        let frame = image::imageops::crop_imm(buffer, 0, 0, w, h).to_image();
+        let image = ColorImage::from_rgba_unmultiplied([w as usize, h as usize], frame.as_raw());
+        let icon = gui_ctx.load_texture(texture, image, TextureOptions::NEAREST);
+        let id = icon.id();
+        self.icons.insert(texture.to_string(), icon);
+        Some(id)
+    }
+
+    //draws a single slot, returns where it was drawn so drops can find it
+    fn cell(&mut self, ui: &mut egui::Ui, texture_map: &TextureMap, slot: &Slot, slot_ref: SlotRef, label: &str, text_size: f32) -> Rect {
+        let size = text_size * CELL_SCALE;
+        let (rect, response) = ui.allocate_exact_size(vec2(size, size), Sense::click_and_drag());
+        let dragged = self.dragging.as_ref().map_or(false, |(from, _)| *from == slot_ref);
+        let icon = slot.item.as_ref().and_then(|item| self.icon(ui.ctx(), texture_map, &item.texture_id));
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, Color32::from_gray(40));
+        if response.hovered() {
+            painter.rect_stroke(rect, 2.0, Stroke::new(1.0, Color32::GOLD));
+        }
+        if let Some(icon) = icon {
+            //the slot being dragged is faded so it is clear where the items came from
+            let tint = if dragged { Color32::from_white_alpha(80) } else { Color32::WHITE };
+            painter.image(icon, rect.shrink(2.0), Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)), tint);
+        } else if !label.is_empty() {
+            painter.text(rect.center(), Align2::CENTER_CENTER, label, FontId::proportional(text_size * 0.6), Color32::GRAY);
+        }
+        if slot.amount > 1 {
+            painter.text(rect.right_bottom() - vec2(2.0, 0.0), Align2::RIGHT_BOTTOM, slot.amount.to_string(), FontId::proportional(text_size * 0.8), Color32::WHITE);
+        }
+        //equipment shows how much durability it has left along the bottom
+        if let Some(item) = slot.item.as_ref().filter(|item| item.has_durability()) {
+            let left = (item.uses as f32 / item.max_uses as f32).clamp(0.0, 1.0);
+            let bar = Rect::from_min_size(rect.left_bottom() + vec2(2.0, -4.0), vec2((rect.width() - 4.0) * left, 2.0));
+            painter.rect_filled(bar, 0.0, condition_color(&item.condition()));
+            if item.is_broken() {
+                painter.rect_filled(rect, 2.0, Color32::from_rgba_unmultiplied(160, 0, 0, 80));
+            }
+        }
+        if response.secondary_clicked() && slot.item.as_ref().map_or(false, |item| item.has_durability()) {
+            self.repairing = Some(slot_ref.clone());
+        }
+        if response.drag_started() && slot.item.is_some() {
+            let half = ui.input(|input| input.modifiers.shift);
+            self.dragging = Some((slot_ref, half));
+        }
+        if let Some(item) = slot.item.as_ref() {
+            response.on_hover_ui(|ui| {
+                let text = |text: String| RichText::new(text).size(text_size);
+                ui.label(text(item.display_name()).strong().color(rarity_color(&item.rarity)));
+                ui.label(text(format!("{:?} {:?}", item.rarity, item.kind)).color(Color32::GRAY));
+                if item.two_handed {
+                    ui.label(text("two handed".to_string()).color(Color32::GRAY));
+                }
+                for (stat, value) in STATS.iter().zip(values(&item.stats)) {
+                    if value != 0 {
+                        ui.label(text(format!("{} {:+}", stat, value)));
+                    }
+                }
+                //rolled stats are listed under the affix they came from
+                for affix in &item.affixes {
+                    ui.label(text(affix.name.clone()).color(Color32::LIGHT_GREEN));
+                    for (stat, value) in STATS.iter().zip(values(&affix.stats)) {
+                        if value != 0 {
+                            ui.label(text(format!("  {} {:+}", stat, value)).color(Color32::LIGHT_GREEN));
+                        }
+                    }
+                }
+                if let Some(effect) = item.effect.as_ref() {
+                    ui.label(text(format!("{} on {:?}", effect.name, effect.trigger)).color(Color32::LIGHT_BLUE));
+                }
+                if item.has_durability() {
+                    ui.label(text(format!("durability: {}/{} ({:?})", item.uses, item.max_uses, item.condition())).color(condition_color(&item.condition())));
+                    if item.uses < item.max_uses {
+                        ui.label(text(format!("right click to repair for {} coins", durability::repair_cost(item))).color(Color32::GRAY));
+                    }
+                } else if item.uses >= 0 {
+                    ui.label(text(format!("uses: {}", item.uses)));
+                }
+                ui.label(text(format!("weight: {:.1}  value: {}", item.weight, item.value)));
+                if !item.uuid.is_nil() {
+                    ui.label(text(format!("#{}", item.uuid.to_string().split('-').next().unwrap_or_default())).color(Color32::DARK_GRAY));
+                }
+            });
+        }
+        rect
+    }
+
+    //draws a slot of the quickbar with the amount carried and the cooldown left, returns where it was drawn
+    fn quick_cell(&mut self, ui: &mut egui::Ui, texture_map: &TextureMap, inventory: &Inventory, index: usize, cooldowns: &HashMap<String, f32>, text_size: f32) -> (Rect, bool) {
+        let size = text_size * CELL_SCALE;
+        let (rect, response) = ui.allocate_exact_size(vec2(size, size), Sense::click());
+        let assigned = inventory.quickbar.get(index).cloned().flatten();
+        let carried = assigned.as_ref().and_then(|id| inventory.find(id)).and_then(|slot| inventory.get(&slot)).and_then(|slot| slot.item.as_ref());
+        let icon = carried.and_then(|item| self.icon(ui.ctx(), texture_map, &item.texture_id));
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, Color32::from_black_alpha(160));
+        if response.hovered() {
+            painter.rect_stroke(rect, 2.0, Stroke::new(1.0, Color32::GOLD));
+        }
+        if let Some(icon) = icon {
+            painter.image(icon, rect.shrink(2.0), Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)), Color32::WHITE);
+        }
+        if let Some(id) = assigned.as_ref() {
+            painter.text(rect.right_bottom() - vec2(2.0, 0.0), Align2::RIGHT_BOTTOM, inventory.count(id).to_string(), FontId::proportional(text_size * 0.8), Color32::WHITE);
+            if let Some(left) = cooldowns.get(id).filter(|left| **left > 0.0) {
+                painter.rect_filled(rect, 2.0, Color32::from_black_alpha(160));
+                painter.text(rect.center(), Align2::CENTER_CENTER, format!("{:.0}", left.ceil()), FontId::proportional(text_size), Color32::WHITE);
+            }
+        }
+        painter.text(rect.left_top() + vec2(2.0, 0.0), Align2::LEFT_TOP, (index + 1).to_string(), FontId::proportional(text_size * 0.6), Color32::GRAY);
+        (rect, response.secondary_clicked())
+    }
+
+    /**
+     * Shows the quickbar, and the bag and equipment of an entity while the window is open
+     * gui_ctx: &egui::Context - The context to show the window in
+     * inventory: &Inventory - The inventory to show
+     * cooldowns: &HashMap<String, f32> - Seconds left before each consumable can be used again
+     * texture_map: &TextureMap - Source of the item icons
+     * text_size: f32 - The size of the text, cells are sized from it
+     * returns: Option<Action> - Items dropped onto another slot or the quickbar, holding shift while dragging takes half of a stack
+     */
+    pub fn show(&mut self, gui_ctx: &egui::Context, inventory: &Inventory, cooldowns: &HashMap<String, f32>, texture_map: &TextureMap, text_size: f32) -> Option<Action> {
+        let mut quick_cells = Vec::new();
+        let mut cleared = None;
+        egui::Area::new("quickbar")
+            .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -text_size))
+            .show(gui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for index in 0..inventory.quickbar.len() {
+                        let (rect, clear) = self.quick_cell(ui, texture_map, inventory, index, cooldowns, text_size);
+                        quick_cells.push((index, rect));
+                        if clear {
+                            cleared = Some(index);
+                        }
+                    }
+                });
+            }
+        );
+        //right clicking a slot of the quickbar takes the consumable off
+        if let Some(index) = cleared {
+            return Some(Action::Assign(index, None));
+        }
+        if !self.visible {
+            return None;
+        }
+        let mut cells = Vec::new();
+        egui::Window::new(RichText::new("inventory").size(text_size).color(Color32::DARK_GRAY))
+            .id(egui::Id::new("inventory"))
+            .resizable(false)
+            .collapsible(false)
+            .show(gui_ctx, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        for name in APPAREL_SLOTS {
+                            if let Some(slot) = inventory.apparel.get(name) {
+                                cells.push((SlotRef::Apparel(name.to_string()), self.cell(ui, texture_map, slot, SlotRef::Apparel(name.to_string()), name, text_size)));
+                            }
+                        }
+                        ui.separator();
+                        for name in HOLDING {
+                            if let Some(slot) = inventory.holding.get(name) {
+                                cells.push((SlotRef::Holding(name.to_string()), self.cell(ui, texture_map, slot, SlotRef::Holding(name.to_string()), name, text_size)));
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        egui::Grid::new("bag").spacing(vec2(2.0, 2.0)).show(ui, |ui| {
+                            let columns = inventory.size.w.max(1) as usize;
+                            for (index, slot) in inventory.bag.iter().enumerate() {
+                                cells.push((SlotRef::Bag(index), self.cell(ui, texture_map, slot, SlotRef::Bag(index), "", text_size)));
+                                if (index + 1) % columns == 0 {
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                        ui.label(RichText::new(format!("weight: {:.1}", inventory.weight())).size(text_size));
+                    });
+                });
+            }
+        );
+        if let Some(slot) = self.repairing.take() {
+            return Some(Action::Repair(slot));
+        }
+        let (from, half) = self.dragging.clone()?;
+        let pointer = gui_ctx.input(|input| input.pointer.interact_pos());
+        let dragged = inventory.get(&from).and_then(|slot| slot.item.as_ref());
+        //the dragged item follows the pointer above everything else
+        if let (Some(pointer), Some(item)) = (pointer, dragged) {
+            if let Some(icon) = self.icon(gui_ctx, texture_map, &item.texture_id) {
+                let size = text_size * CELL_SCALE;
+                egui::Area::new("dragged_item")
+                    .order(Order::Tooltip)
+                    .interactable(false)
+                    .fixed_pos(pointer - vec2(size, size) / 2.0)
+                    .show(gui_ctx, |ui| {
+                        ui.image(icon, vec2(size, size));
+                    }
+                );
+            }
+        }
+        if !gui_ctx.input(|input| input.pointer.any_released()) {
+            return None;
+        }
+        self.dragging = None;
+        let pointer = pointer?;
+        //only consumables go on the quickbar, the items stay where they are
+        if let Some((index, _)) = quick_cells.into_iter().find(|(_, rect)| rect.contains(pointer)) {
+            return dragged
+                .filter(|item| item.kind == Kind::Consumable)
+                .map(|item| Action::Assign(index, Some(item.id.clone())));
+        }
+        let to = cells.into_iter().find(|(_, rect)| rect.contains(pointer)).map(|(slot, _)| slot)?;
+        let amount = if half {
+            inventory.get(&from).map(|slot| (slot.amount + 1) / 2)
+        } else {
+            None
+        };
+        Some(Action::Move(Move { from, to, amount }))
+    }
+}
\ No newline at end of file
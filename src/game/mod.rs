@@ -1,2 +1,10 @@
+pub mod batch;
+pub mod character_sheet;
+pub mod chunk_cache;
 pub mod configuration;
-pub mod scene;
\ No newline at end of file
+pub mod input;
+pub mod inventory_screen;
+pub mod profiler;
+pub mod quest_log;
+pub mod scene;
+pub mod spellbook;
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+use egui::{RichText, Color32};
+
+use super::chunk_cache::CHUNK_BYTES;
+
+//Number of frames the averages are taken over
+const SAMPLES: usize = 120;
+
+//Frame timings shown in the profiler overlay, toggled with F3
+pub struct Profiler {
+    pub visible: bool,
+    //frame times in milliseconds, used as a ring buffer
+    frames: [f32; SAMPLES],
+    index: usize,
+    filled: usize,
+    start: Option<Instant>,
+    update_time: f32,
+    draw_time: f32,
+    draw_calls: usize,
+    sprites: usize,
+    chunk_images: usize,
+    entities: usize,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frames: [0.0; SAMPLES],
+            index: 0,
+            filled: 0,
+            start: None,
+            update_time: 0.0,
+            draw_time: 0.0,
+            draw_calls: 0,
+            sprites: 0,
+            chunk_images: 0,
+            entities: 0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    //records how long the whole last frame took
+    pub fn push_frame(&mut self, delta: Duration) {
+        self.frames[self.index] = delta.as_secs_f32() * 1000.0;
+        self.index = (self.index + 1) % SAMPLES;
+        self.filled = (self.filled + 1).min(SAMPLES);
+    }
+
+    pub fn begin(&mut self) {
+        self.start = Some(Instant::now());
+    }
+
+    //milliseconds since begin
+    fn elapsed(&mut self) -> f32 {
+        if let Some(start) = self.start.take() {
+            start.elapsed().as_secs_f32() * 1000.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn end_update(&mut self) {
+        self.update_time = self.elapsed();
+    }
+
+    pub fn end_draw(&mut self, draw_calls: usize, sprites: usize, chunk_images: usize, entities: usize) {
+        self.draw_time = self.elapsed();
+        self.draw_calls = draw_calls;
+        self.sprites = sprites;
+        self.chunk_images = chunk_images;
+        self.entities = entities;
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.filled == 0 {
+            return 0.0;
+        }
+        self.frames[..self.filled].iter().sum::<f32>() / self.filled as f32
+    }
+
+    pub fn worst(&self) -> f32 {
+        self.frames[..self.filled].iter().cloned().fold(0.0, f32::max)
+    }
+
+    //draw timings are from the previous frame, since the gui is built before drawing
+    pub fn show(&self, gui_ctx: &egui::Context, text_size: f32) {
+        if !self.visible {
+            return;
+        }
+        let average = self.average();
+        let worst = self.worst();
+        egui::Area::new("profiler")
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(0.0, 0.0))
+            .show(gui_ctx, |ui| {
+                let color = if worst > 1000.0 / 30.0 { Color32::LIGHT_RED } else { Color32::LIGHT_GREEN };
+                ui.label(RichText::new(format!("frame: {:.2}ms avg, {:.2}ms worst", average, worst)).size(text_size).color(color));
+                ui.label(RichText::new(format!("update: {:.2}ms draw: {:.2}ms", self.update_time, self.draw_time)).size(text_size));
+                ui.label(RichText::new(format!("draw calls: {} sprites: {}", self.draw_calls, self.sprites)).size(text_size));
+                ui.label(RichText::new(format!("entities: {}", self.entities)).size(text_size));
+                ui.label(RichText::new(format!("chunk images: {} ({:.1}MiB)", self.chunk_images, self.chunk_images as f32 * CHUNK_BYTES as f32 / (1024.0 * 1024.0))).size(text_size));
+            }
+        );
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
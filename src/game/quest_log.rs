@@ -0,0 +1,67 @@
+use egui::{RichText, Color32};
+
+use super::scene::mission::{Journal, Status};
+
+//Quest log toggled with the journal key, lists the missions that have started
+#[derive(Debug, Default)]
+pub struct QuestLog {
+    pub visible: bool,
+}
+
+impl QuestLog {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /**
+     * Shows the active missions and what is left to do, finished ones are listed under them
+     * gui_ctx: &egui::Context - The context to show the window in
+     * journal: &Journal - The missions and their progress
+     * text_size: f32 - The size of the text
+     */
+    pub fn show(&self, gui_ctx: &egui::Context, journal: &Journal, text_size: f32) {
+        if !self.visible {
+            return;
+        }
+        let text = |text: String| RichText::new(text).size(text_size);
+        //the journal is a hashmap, sorting keeps the list from jumping around
+        let mut entries = journal.entries.values()
+            .filter(|entry| entry.status != Status::Inactive)
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| (a.status == Status::Complete, &a.mission.name).cmp(&(b.status == Status::Complete, &b.mission.name)));
+        egui::Window::new(RichText::new("Quest Log").size(text_size).color(Color32::DARK_GRAY))
+            .id(egui::Id::new("quest_log"))
+            .resizable(false)
+            .collapsible(false)
+            .show(gui_ctx, |ui| {
+                if entries.is_empty() {
+                    ui.label(text("no missions".to_string()).color(Color32::GRAY));
+                }
+                for entry in entries {
+                    let complete = entry.status == Status::Complete;
+                    let color = if complete { Color32::GRAY } else { Color32::GOLD };
+                    ui.label(text(entry.mission.name.clone()).strong().color(color));
+                    if let Some(location) = journal.names.get(&entry.mission.location) {
+                        ui.label(text(location.clone()).color(Color32::GRAY));
+                    }
+                    if complete {
+                        ui.label(text("complete".to_string()).color(Color32::GRAY));
+                    } else {
+                        ui.label(text(entry.describe(&journal.names)));
+                        let reward = &entry.mission.reward;
+                        let mut gives = Vec::new();
+                        if reward.experience > 0 {
+                            gives.push(format!("{} experience", reward.experience));
+                        }
+                        for item in &reward.items {
+                            gives.push(format!("{} {}", item.amount, journal.names.get(&item.id).unwrap_or(&item.id)));
+                        }
+                        if !gives.is_empty() {
+                            ui.label(text(format!("reward: {}", gives.join(", "))).color(Color32::LIGHT_BLUE));
+                        }
+                    }
+                    ui.separator();
+                }
+            });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,207 @@
+use rand::Rng;
+
+use crate::core::{data::affinity::AffinityTable, Direction, Motion};
+
+use super::{world::{World, EntityId}, components::AiState, combat::{self, Attack, Hit, is_hostile}, navigation::{Navigation, to_tile, to_position}};
+
+//Seconds between working out a new path to a moving target
+const REPATH: f32 = 0.5;
+//Longest and shortest seconds a mob idles for
+const IDLE: (f32, f32) = (1.0, 4.0);
+//Tiles from home a wandering mob walks to
+const WANDER_RADIUS: i32 = 4;
+//Wandering is slower than chasing
+const WANDER_SPEED: f32 = 0.5;
+//Hostile mobs run once their health drops below this fraction
+const FLEE_HEALTH: f32 = 0.25;
+//Chunks a mob stays away from its target to swing at it
+const ATTACK_REACH: f32 = 0.12;
+//Chunks close enough to a tile to count as standing on it
+const ARRIVED: f32 = 0.02;
+//Chunks a following mob keeps from its leader
+const FOLLOW_DISTANCE: f32 = 0.15;
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+//nearest entity the mob would attack within its perception
+fn perceive(world: &World, id: EntityId, origin: (f32, f32)) -> Option<(EntityId, f32)> {
+    let perception = world.sheets.get(&id).map_or(0.0, |sheet| sheet.derived.perception);
+    world.positions.iter()
+        .filter(|(other, _)| is_hostile(world, id, **other))
+        .map(|(other, position)| (*other, distance(origin, position.get())))
+        .filter(|(_, distance)| *distance <= perception)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+//moves an entity towards a position without walking into walls or solid entities, returns whether it got there
+fn walk(world: &mut World, navigation: &Navigation, id: EntityId, goal: (f32, f32), amount: f32) -> bool {
+    let mut moved = if let Some(position) = world.positions.get(&id) {
+        position.clone()
+    } else {
+        return true;
+    };
+    let (x, y) = (goal.0 - moved.x, goal.1 - moved.y);
+    let length = (x * x + y * y).sqrt();
+    if length <= ARRIVED {
+        return true;
+    }
+    let step = amount.min(length);
+    moved.x += x / length * step;
+    moved.y += y / length * step;
+    moved.direction = if x.abs() > y.abs() {
+        if x > 0.0 { Direction::Right } else { Direction::Left }
+    } else if y > 0.0 { Direction::Down } else { Direction::Up };
+    if navigation.is_walkable(to_tile(moved.get())) && !world.is_blocked(id, &moved) {
+        world.positions.insert(id, moved);
+    }
+    step >= length
+}
+
+//walks along the path of a mob, returns whether there is nothing left to walk
+fn follow(world: &mut World, navigation: &Navigation, id: EntityId, amount: f32) -> bool {
+    let next = if let Some(next) = world.ais.get(&id).and_then(|ai| ai.path.last().cloned()) {
+        next
+    } else {
+        return true;
+    };
+    if walk(world, navigation, id, to_position(next), amount) {
+        if let Some(ai) = world.ais.get_mut(&id) {
+            ai.path.pop();
+            return ai.path.is_empty();
+        }
+    }
+    false
+}
+
+//entity a state walks after or swings at
+fn pursued(state: &AiState) -> Option<EntityId> {
+    match state {
+        AiState::Chase(target) | AiState::Attack(target) | AiState::Follow(target) => Some(*target),
+        _ => None,
+    }
+}
+
+//walks after a moving goal, working out a new path every REPATH seconds and heading straight at it without one
+fn pursue(world: &mut World, navigation: &Navigation, id: EntityId, origin: (f32, f32), goal: (f32, f32), amount: f32) {
+    if let Some(ai) = world.ais.get_mut(&id).filter(|ai| ai.timer <= 0.0) {
+        //an unreachable goal leaves the path empty, it is not searched for again until the timer runs out
+        ai.path = navigation.find_path(to_tile(origin), to_tile(goal)).unwrap_or_default();
+        ai.timer = REPATH;
+    }
+    if follow(world, navigation, id, amount) {
+        //on the same tile or with no path, close in directly
+        walk(world, navigation, id, goal, amount);
+    }
+}
+
+/**
+ * Runs every mob, picking a state from what it perceives and acting on it
+ * world: &mut World - The world the mobs are in
+ * navigation: &Navigation - The walkable tiles of the dungeon
+ * affinities: &AffinityTable - Passed on to the attacks of the mobs
+ * delta: f32 - Seconds since the last update
+ * returns: Vec<Hit> - The damage the mobs dealt
+ */
+pub fn update(world: &mut World, navigation: &Navigation, affinities: &AffinityTable, delta: f32) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    let mut rng = rand::thread_rng();
+    let mobs = world.ais.keys().cloned().collect::<Vec<_>>();
+    for id in mobs {
+        let origin = if let Some(position) = world.positions.get(&id) {
+            position.get()
+        } else {
+            continue;
+        };
+        let speed = world.sheets.get(&id).map_or(0.0, |sheet| sheet.derived.move_speed) * delta;
+        let hurt = world.healths.get(&id).map_or(false, |health| health.max > 0 && (health.current as f32) < health.max as f32 * FLEE_HEALTH);
+        let seen = perceive(world, id, origin);
+        let leader = world.ais.get(&id).and_then(|ai| ai.leader).filter(|leader| world.positions.contains_key(leader));
+        //friendly mobs never perceive anything hostile, so they only idle, wander and follow
+        let state = if let Some(ai) = world.ais.get_mut(&id) {
+            ai.timer -= delta;
+            let state = match (seen, &ai.state) {
+                (Some((target, _)), _) if hurt => AiState::Flee(target),
+                (Some((target, distance)), _) if distance <= ATTACK_REACH => AiState::Attack(target),
+                (Some((target, _)), _) => AiState::Chase(target),
+                (None, AiState::Wander) => AiState::Wander,
+                (None, AiState::Idle) => AiState::Idle,
+                (None, AiState::Follow(leader)) => AiState::Follow(*leader),
+                //lost sight of the target
+                (None, _) => {
+                    ai.path.clear();
+                    AiState::Idle
+                },
+            };
+            //a leader takes over from wandering, losing it goes back to idling
+            let state = match (leader, state) {
+                (Some(leader), AiState::Idle | AiState::Wander | AiState::Follow(_)) => AiState::Follow(leader),
+                (None, AiState::Follow(_)) => {
+                    ai.path.clear();
+                    AiState::Idle
+                },
+                (_, state) => state,
+            };
+            //a new target is worth a path straight away, after that only every REPATH seconds
+            if pursued(&state) != pursued(&ai.state) {
+                ai.timer = 0.0;
+            }
+            ai.state = state.clone();
+            state
+        } else {
+            continue;
+        };
+        let mut walking = true;
+        match state {
+            AiState::Idle => {
+                walking = false;
+                let ai = world.ais.get_mut(&id).unwrap();
+                if ai.timer <= 0.0 {
+                    //pick somewhere near home to walk to
+                    let home = to_tile(ai.home);
+                    let goal = (home.0 + rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS), home.1 + rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS));
+                    if let Some(path) = navigation.find_path(to_tile(origin), goal) {
+                        ai.path = path;
+                        ai.state = AiState::Wander;
+                    }
+                    ai.timer = rng.gen_range(IDLE.0..IDLE.1);
+                }
+            },
+            AiState::Wander => {
+                if follow(world, navigation, id, speed * WANDER_SPEED) {
+                    let ai = world.ais.get_mut(&id).unwrap();
+                    ai.state = AiState::Idle;
+                    ai.timer = rng.gen_range(IDLE.0..IDLE.1);
+                }
+            },
+            AiState::Chase(target) => {
+                let goal = world.positions.get(&target).map(|position| position.get()).unwrap_or(origin);
+                pursue(world, navigation, id, origin, goal, speed);
+            },
+            AiState::Attack(target) => {
+                walking = false;
+                let goal = world.positions.get(&target).map(|position| position.get()).unwrap_or(origin);
+                hits.extend(combat::attack(world, affinities, id, Attack::Melee, (goal.0 - origin.0, goal.1 - origin.1)));
+            },
+            AiState::Follow(leader) => {
+                let goal = world.positions.get(&leader).map(|position| position.get()).unwrap_or(origin);
+                if distance(origin, goal) <= FOLLOW_DISTANCE {
+                    walking = false;
+                } else {
+                    pursue(world, navigation, id, origin, goal, speed);
+                }
+            },
+            AiState::Flee(target) => {
+                let from = world.positions.get(&target).map(|position| position.get()).unwrap_or(origin);
+                let away = (origin.0 - from.0, origin.1 - from.1);
+                let length = (away.0 * away.0 + away.1 * away.1).sqrt().max(f32::EPSILON);
+                walk(world, navigation, id, (origin.0 + away.0 / length, origin.1 + away.1 / length), speed);
+            },
+        }
+        if let Some(sprite) = world.sprites.get_mut(&id) {
+            sprite.motion = if walking { Motion::Walk } else { Motion::Idle };
+        }
+    }
+    hits
+}
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use ggez::graphics;
+
+use crate::core::{toml_loader::{Animation, Clip, Rect}, logger::error, Direction, Motion};
+
+//Where a frame is in the texture map
+#[derive(Clone, Debug)]
+pub struct Frame {
+    //normalized to the size of the texture map
+    pub src: graphics::Rect,
+    //size in pixels
+    pub w: f32,
+    pub h: f32,
+    pub flip: bool,
+}
+
+//Frames of a clip, ready to be drawn from the texture map
+#[derive(Clone, Debug)]
+struct Track {
+    frames: Vec<Frame>,
+    fps: f32,
+    looping: bool,
+}
+
+impl Track {
+    fn new(rect: &Rect, atlas: (f32, f32), animation: &Animation, clip: &Clip) -> Self {
+        let (w, h) = (animation.frame.w, animation.frame.h);
+        //profiles are counted left to right, then top to bottom
+        let columns = rect.w as u32 / w;
+        let profiles = columns * (rect.h as u32 / h);
+        let mut frames = Vec::new();
+        for profile in &clip.frames {
+            if *profile >= profiles {
+                error!("Profile {} is outside of the texture, which only has {} profiles", profile, profiles);
+                continue;
+            }
+            let x = rect.x + (profile % columns * w) as f32;
+            let y = rect.y + (profile / columns * h) as f32;
+            frames.push(Frame {
+                src: graphics::Rect::new(x / atlas.0, y / atlas.1, w as f32 / atlas.0, h as f32 / atlas.1),
+                w: w as f32,
+                h: h as f32,
+                flip: clip.flip,
+            });
+        }
+        Self {
+            frames,
+            fps: clip.fps,
+            looping: clip.looping,
+        }
+    }
+}
+
+//Picks the frame to draw from the direction and motion of its owner
+#[derive(Clone, Debug, Default)]
+pub struct Animator {
+    tracks: HashMap<(Motion, Direction), Track>,
+    motion: Motion,
+    direction: Direction,
+    frame: usize,
+    elapsed: f32,
+}
+
+impl Animator {
+    /**
+     * Cuts a texture into profiles and builds a track for each clip of the animation
+     * rect: &Rect - Where the texture is in the texture map
+     * atlas: (f32, f32) - The size of the texture map in pixels
+     * animation: &Animation - The profile layout and clips of the texture
+     * returns: Animator - An idle animator facing down
+     */
+    pub fn new(rect: &Rect, atlas: (f32, f32), animation: &Animation) -> Self {
+        let mut animator = Self::default();
+        for clip in &animation.clips {
+            animator.tracks.insert((clip.motion.clone(), clip.direction.clone()), Track::new(rect, atlas, animation, clip));
+        }
+        animator
+    }
+
+    //an animator that always shows the whole texture, for textures without an animation
+    pub fn still(rect: &Rect, atlas: (f32, f32)) -> Self {
+        let mut animator = Self::default();
+        let track = Track {
+            frames: vec![Frame {
+                src: graphics::Rect::new(rect.x / atlas.0, rect.y / atlas.1, rect.w / atlas.0, rect.h / atlas.1),
+                w: rect.w,
+                h: rect.h,
+                flip: false,
+            }],
+            fps: 0.0,
+            looping: false,
+        };
+        animator.tracks.insert((Motion::Idle, Direction::Down), track);
+        animator
+    }
+
+    //switches clips, restarting the clip only when something changed
+    pub fn set(&mut self, motion: Motion, direction: Direction) {
+        if self.motion != motion || self.direction != direction {
+            self.motion = motion;
+            self.direction = direction;
+            self.frame = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    //delta is in seconds
+    pub fn update(&mut self, delta: f32) {
+        let (count, fps, looping) = if let Some(track) = self.track() {
+            (track.frames.len(), track.fps, track.looping)
+        } else {
+            return;
+        };
+        if fps <= 0.0 || count <= 1 {
+            return;
+        }
+        self.elapsed += delta;
+        let frame_time = 1.0 / fps;
+        while self.elapsed >= frame_time {
+            self.elapsed -= frame_time;
+            if self.frame + 1 < count {
+                self.frame += 1;
+            } else if looping {
+                self.frame = 0;
+            }
+        }
+    }
+
+    pub fn get_frame(&self) -> Option<&Frame> {
+        let track = self.track()?;
+        track.frames.get(self.frame).or_else(|| track.frames.first())
+    }
+
+    //missing clips fall back to idling in the same direction, then to idling facing down
+    fn track(&self) -> Option<&Track> {
+        self.tracks.get(&(self.motion.clone(), self.direction.clone()))
+            .or_else(|| self.tracks.get(&(Motion::Idle, self.direction.clone())))
+            .or_else(|| self.tracks.get(&(Motion::Idle, Direction::Down)))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,169 @@
+use ggez::glam;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::core::toml_loader::{Settings, Size};
+
+//Fraction of the distance to the target covered per second, higher is snappier
+const SMOOTHING: f32 = 8.0;
+//Amount of zoom per step of the mouse wheel
+const ZOOM_STEP: f32 = 0.25;
+//Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+//Offset in pixels at full trauma
+const MAX_SHAKE: f32 = 24.0;
+
+//Looks at the dungeon, positions are in chunks and the screen is in pixels
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Camera {
+    //center of the view
+    pub position: (f32, f32),
+    //where the camera is heading
+    pub target: (f32, f32),
+    //1.0 shows exactly settings.fit chunks, lower values zoom out
+    pub zoom: f32,
+    //0 to 1, the shake grows with the square of the trauma
+    pub trauma: f32,
+    //size of the dungeon
+    pub bounds: (f32, f32),
+    //chunk images the cache can hold, the camera never zooms out further than that many chunks stream in
+    pub capacity: usize,
+    #[serde(skip)]
+    shake: (f32, f32),
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: (0.0, 0.0),
+            target: (0.0, 0.0),
+            zoom: 1.0,
+            trauma: 0.0,
+            bounds: (0.0, 0.0),
+            capacity: usize::MAX,
+            shake: (0.0, 0.0),
+        }
+    }
+}
+
+impl Camera {
+    pub fn new(position: (f32, f32), bounds: &Size, capacity: usize) -> Self {
+        Self {
+            position,
+            target: position,
+            bounds: (bounds.w as f32, bounds.h as f32),
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    /**
+     * Lowest zoom at which the chunks streamed around the view still fit in the capacity
+     *  the view is fit / zoom chunks, streaming adds a chunk it straddles and a margin of one on every side
+     * settings: &Settings - Source of the fit
+     * returns: f32 - The zoom, 1.0 when not even the fit fits
+     */
+    fn widest(&self, settings: &Settings) -> f32 {
+        let (w, h) = (settings.fit.w as f32, settings.fit.h as f32);
+        //(w / zoom + 3) * (h / zoom + 3) <= capacity, solved for 1 / zoom
+        let (a, b, c) = (w * h, 3.0 * (w + h), 9.0 - self.capacity as f32);
+        let inverse = (-b + (b * b - 4.0 * a * c).max(0.0).sqrt()) / (2.0 * a);
+        if inverse <= 0.0 { 1.0 } else { (1.0 / inverse).min(1.0) }
+    }
+
+    pub fn follow(&mut self, target: (f32, f32)) {
+        self.target = target;
+    }
+
+    //adds trauma, which is how combat shakes the screen
+    pub fn shake(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /**
+     * Zooms by a number of steps, never showing fewer chunks than settings.fit
+     *  nor more than the dungeon has or the chunk cache can hold
+     * steps: f32 - Positive steps zoom in, negative steps zoom out
+     * settings: &Settings - Source of the fit
+     */
+    pub fn zoom_by(&mut self, steps: f32, settings: &Settings) {
+        let smallest = (settings.fit.w as f32 / self.bounds.0).max(settings.fit.h as f32 / self.bounds.1).max(self.widest(settings)).min(1.0);
+        self.zoom = (self.zoom + steps * ZOOM_STEP).clamp(smallest, 1.0);
+    }
+
+    //delta is in seconds
+    pub fn update(&mut self, delta: f32, settings: &Settings) {
+        //frame rate independent easing towards the target
+        let ease = 1.0 - (-SMOOTHING * delta).exp();
+        self.position.0 += (self.target.0 - self.position.0) * ease;
+        self.position.1 += (self.target.1 - self.position.1) * ease;
+        self.clamp(settings);
+
+        self.trauma = (self.trauma - TRAUMA_DECAY * delta).max(0.0);
+        let strength = MAX_SHAKE * self.trauma * self.trauma;
+        self.shake = if strength > 0.0 {
+            let mut rng = rand::thread_rng();
+            (strength * rng.gen_range(-1.0..=1.0), strength * rng.gen_range(-1.0..=1.0))
+        } else {
+            (0.0, 0.0)
+        };
+    }
+
+    //keeps the view inside the dungeon, centering it when the dungeon is smaller than the view
+    fn clamp(&mut self, settings: &Settings) {
+        let view = self.view(settings);
+        let bounds = self.bounds;
+        let clamp_axis = |position: f32, view: f32, bound: f32| {
+            if view >= bound {
+                bound / 2.0
+            } else {
+                position.clamp(view / 2.0, bound - view / 2.0)
+            }
+        };
+        self.position.0 = clamp_axis(self.position.0, view.0, bounds.0);
+        self.position.1 = clamp_axis(self.position.1, view.1, bounds.1);
+    }
+
+    //pixels a chunk takes up on screen
+    pub fn chunk_size(&self, settings: &Settings) -> f32 {
+        settings.render_size * self.zoom
+    }
+
+    //how much textures are scaled by
+    pub fn scale(&self, settings: &Settings) -> f32 {
+        settings.render_scale * self.zoom
+    }
+
+    //size of the view in chunks
+    pub fn view(&self, settings: &Settings) -> (f32, f32) {
+        let size = self.chunk_size(settings);
+        (settings.resolution.w as f32 / size, settings.resolution.h as f32 / size)
+    }
+
+    //chunks that are at least partially on screen as start and end corners, margin adds chunks on every side
+    pub fn visible_chunks(&self, settings: &Settings, margin: u32) -> ((u32, u32), (u32, u32)) {
+        let view = self.view(settings);
+        let start_x = (self.position.0 - view.0 / 2.0).floor().max(0.0) as u32;
+        let start_y = (self.position.1 - view.1 / 2.0).floor().max(0.0) as u32;
+        let end_x = ((self.position.0 + view.0 / 2.0).ceil().max(0.0) as u32 + margin).min(self.bounds.0 as u32);
+        let end_y = ((self.position.1 + view.1 / 2.0).ceil().max(0.0) as u32 + margin).min(self.bounds.1 as u32);
+        ((start_x.saturating_sub(margin), start_y.saturating_sub(margin)), (end_x, end_y))
+    }
+
+    pub fn world_to_screen(&self, world: (f32, f32), settings: &Settings) -> glam::Vec2 {
+        let size = self.chunk_size(settings);
+        glam::Vec2::new(
+            (world.0 - self.position.0) * size + settings.resolution.w as f32 / 2.0 + self.shake.0,
+            (world.1 - self.position.1) * size + settings.resolution.h as f32 / 2.0 + self.shake.1
+        )
+    }
+
+    //ignores the shake, so aiming does not jitter with the screen
+    pub fn screen_to_world(&self, screen: glam::Vec2, settings: &Settings) -> (f32, f32) {
+        let size = self.chunk_size(settings);
+        (
+            (screen.x - settings.resolution.w as f32 / 2.0) / size + self.position.0,
+            (screen.y - settings.resolution.h as f32 / 2.0) / size + self.position.1
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,328 @@
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::core::{data::{effect::Trigger, affinity::AffinityTable, item::Kind}, toml_loader::CHUNK_SIZE};
+
+use super::{world::{World, EntityId}, entity::Item, navigation::Navigation, status, durability, magic};
+
+//Reach of a melee swing in chunks before the range of the weapon
+const MELEE_REACH: f32 = 0.15;
+//Half of the angle a melee swing covers, in radians
+const MELEE_ARC: f32 = std::f32::consts::FRAC_PI_3;
+//Width of a ranged shot in chunks
+const RANGED_WIDTH: f32 = 0.05;
+//Seconds an entity cannot be hurt after being hit
+const INVULNERABLE: f32 = 0.5;
+//Chunks per second an entity is pushed back when hit
+const KNOCKBACK: f32 = 1.5;
+//Knockback lost per second, as a fraction of the current speed
+const KNOCKBACK_DRAG: f32 = 10.0;
+//Damage multiplier of a critical hit
+const CRITICAL: f32 = 2.0;
+//Texture pixels between two entities that still count as touching
+const CONTACT_SLACK: f32 = 4.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Attack {
+    //hits everything in an arc in front of the attacker, bound to attack
+    Melee,
+    //hits the first thing in a line, bound to use
+    Ranged,
+    //hits the first thing in a line like a shot, but from intelligence and stopped by shields
+    Magic,
+}
+
+//State of an entity that can fight
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Combatant {
+    //seconds until the next attack
+    pub cooldown: f32,
+    //seconds until the entity can be hurt again
+    pub invulnerable: f32,
+    //chunks per second the entity is being pushed
+    pub knockback: (f32, f32),
+    //last entity that hurt this one, credited when it dies
+    pub attacker: Option<EntityId>,
+}
+
+//Damage dealt to a target
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub attacker: EntityId,
+    pub target: EntityId,
+    pub damage: i32,
+    pub critical: bool,
+}
+
+//hand holding the first weapon that is not broken
+pub fn weapon_hand(world: &World, id: EntityId) -> Option<String> {
+    let inventory = world.inventories.get(&id)?;
+    let mut holding = inventory.holding.values().collect::<Vec<_>>();
+    //left hand first, so the same weapon is used every time
+    holding.sort_by(|a, b| a.name.cmp(&b.name));
+    holding.into_iter()
+        .find(|slot| slot.item.as_ref().map_or(false, |item| {
+            item.kind == Kind::Weapon && !item.is_broken() && item.effect.as_ref().map_or(false, |effect| matches!(effect.trigger, Trigger::Contact))
+        }))
+        .map(|slot| slot.name.clone())
+}
+
+fn weapon(world: &World, id: EntityId) -> Option<&Item> {
+    let hand = weapon_hand(world, id)?;
+    world.inventories.get(&id)?.holding.get(&hand)?.item.as_ref()
+}
+
+//chunks an attack of the entity reaches, from its weapon for melee and its range for everything else
+pub fn reach(world: &World, id: EntityId, kind: Attack) -> f32 {
+    match kind {
+        Attack::Melee => MELEE_REACH + weapon(world, id).map_or(0, |item| item.total_stats().rng).max(0) as f32 / CHUNK_SIZE,
+        Attack::Ranged | Attack::Magic => MELEE_REACH + world.sheets.get(&id).map_or(0, |sheet| sheet.total.rng).max(0) as f32 / CHUNK_SIZE,
+    }
+}
+
+//whether the attacker is allowed to hurt the target
+pub fn is_hostile(world: &World, attacker: EntityId, target: EntityId) -> bool {
+    if attacker == target || !world.healths.contains_key(&target) {
+        return false;
+    }
+    match (world.ais.get(&attacker), world.ais.get(&target)) {
+        //mobs do not fight each other
+        (Some(_), Some(_)) => false,
+        (Some(ai), None) => !ai.friendly,
+        (None, Some(ai)) => !ai.friendly,
+        (None, None) => false,
+    }
+}
+
+/**
+ * Attacks in a direction if the attacker is not cooling down
+ * world: &mut World - The world the attacker is in
+ * affinities: &AffinityTable - How the affinities of the attacker and targets change the damage
+ * attacker: EntityId - The entity attacking
+ * kind: Attack - Whether to swing or shoot
+ * aim: (f32, f32) - The direction to attack in, does not need to be normalized
+ * returns: Vec<Hit> - The damage dealt, empty when nothing was hit or the attack was not ready
+ */
+pub fn attack(world: &mut World, affinities: &AffinityTable, attacker: EntityId, kind: Attack, aim: (f32, f32)) -> Vec<Hit> {
+    let ready = world.combatants.get(&attacker).map_or(false, |combatant| combatant.cooldown <= 0.0);
+    let (origin, sheet) = if let (true, Some(position), Some(sheet)) = (ready, world.positions.get(&attacker), world.sheets.get(&attacker)) {
+        (position.get(), sheet)
+    } else {
+        return Vec::new();
+    };
+    let length = (aim.0 * aim.0 + aim.1 * aim.1).sqrt();
+    if length == 0.0 {
+        return Vec::new();
+    }
+    let aim = (aim.0 / length, aim.1 / length);
+    let cooldown = 1.0 / sheet.derived.attack_speed.max(0.1);
+    let reach = reach(world, attacker, kind);
+    let hits = sweep(world, affinities, attacker, kind, origin, aim, reach);
+    if let Some(combatant) = world.combatants.get_mut(&attacker) {
+        combatant.cooldown = cooldown;
+    }
+    hits
+}
+
+/**
+ * Hits whatever is inside the shape of an attack, without waiting for or starting a cooldown
+ * world: &mut World - The world the attacker is in
+ * affinities: &AffinityTable - How the affinities of the attacker and targets change the damage
+ * attacker: EntityId - The entity attacking
+ * kind: Attack - The shape and damage of the attack
+ * origin: (f32, f32) - Where the attack starts in chunks
+ * aim: (f32, f32) - The normalized direction to attack in
+ * reach: f32 - How far the attack goes in chunks
+ * returns: Vec<Hit> - The damage dealt
+ */
+pub fn sweep(world: &mut World, affinities: &AffinityTable, attacker: EntityId, kind: Attack, origin: (f32, f32), aim: (f32, f32), reach: f32) -> Vec<Hit> {
+    let mut targets = Vec::new();
+    for (target, position) in world.positions.iter() {
+        if !is_hostile(world, attacker, *target) {
+            continue;
+        }
+        //targets are hit by the edge of their collider, not only their center
+        let radius = world.colliders.get(target).map_or(0.0, |collider| {
+            let size = collider.size();
+            size.0.max(size.1) / 2.0
+        });
+        let offset = (position.x - origin.0, position.y - origin.1);
+        //distance along the aim and away from it
+        let along = offset.0 * aim.0 + offset.1 * aim.1;
+        let across = (offset.0 * aim.1 - offset.1 * aim.0).abs();
+        let distance = (offset.0 * offset.0 + offset.1 * offset.1).sqrt();
+        let inside = match kind {
+            Attack::Melee => distance - radius <= reach && (distance <= radius || along / distance >= MELEE_ARC.cos()),
+            Attack::Ranged | Attack::Magic => along >= 0.0 && along - radius <= reach && across <= RANGED_WIDTH + radius,
+        };
+        if inside {
+            targets.push((*target, along));
+        }
+    }
+    if kind != Attack::Melee {
+        //shots stop at the first target
+        targets.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        targets.truncate(1);
+    }
+    targets.into_iter()
+        .filter_map(|(target, _)| strike(world, affinities, attacker, target, kind, aim))
+        .collect()
+}
+
+/**
+ * Scales damage by the affinities of the attacker and the target and takes off what the target mitigates, hits and effects both go through it
+ * world: &World - The world the target is in
+ * affinities: &AffinityTable - How the affinities of the attacker and target change the damage
+ * attacker: Option<EntityId> - The entity dealing the damage, None leaves the affinities out
+ * target: EntityId - The entity taking the damage
+ * damage: f32 - The damage before the target defends itself
+ * returns: f32 - The damage that lands, not rounded
+ */
+pub fn harm(world: &World, affinities: &AffinityTable, attacker: Option<EntityId>, target: EntityId, damage: f32) -> f32 {
+    let affinity = match (attacker.and_then(|attacker| world.affinities.get(&attacker)), world.affinities.get(&target)) {
+        (Some(attacking), Some(defending)) => affinities.multiplier(attacking, defending),
+        _ => 1.0,
+    };
+    let mitigation = world.sheets.get(&target).map_or(0.0, |sheet| sheet.derived.mitigation);
+    damage * affinity * (1.0 - mitigation)
+}
+
+//deals damage to a single target, pushing it along the direction of the hit
+pub fn strike(world: &mut World, affinities: &AffinityTable, attacker: EntityId, target: EntityId, kind: Attack, direction: (f32, f32)) -> Option<Hit> {
+    if world.combatants.get(&target).map_or(false, |combatant| combatant.invulnerable > 0.0) {
+        return None;
+    }
+    let sheet = world.sheets.get(&attacker)?;
+    let mut rng = rand::thread_rng();
+    //strength swings, dexterity aims
+    let base = match kind {
+        Attack::Melee => sheet.total.stg,
+        Attack::Ranged => sheet.total.dex,
+        Attack::Magic => sheet.total.int,
+    } as f32 / 2.0;
+    //the weapon effect rolls how much of the weapon's stats land, only swings use the weapon
+    let held = if kind == Attack::Melee { weapon(world, attacker) } else { None };
+    let bonus = if let Some(item) = held {
+        let effect = item.effect.as_ref()?;
+        if rng.gen::<f32>() < effect.chance {
+            item.total_stats().stg as f32 * rng.gen_range(effect.min.min(effect.max)..=effect.max.max(effect.min))
+        } else {
+            0.0
+        }
+    } else {
+        0.0
+    };
+    let critical = rng.gen::<f32>() < sheet.derived.crit;
+    let mut damage = base + bonus;
+    if critical {
+        damage *= CRITICAL;
+    }
+    let mut damage = (harm(world, affinities, Some(attacker), target, damage).round() as i32).max(1);
+    //shields soak up magic before it reaches the health of the target
+    if let Some(shield) = world.shields.get_mut(&target).filter(|_| kind == Attack::Magic) {
+        let absorbed = damage.min(shield.absorb);
+        shield.absorb -= absorbed;
+        damage -= absorbed;
+        if shield.absorb <= 0 {
+            world.shields.remove(&target);
+        }
+    }
+
+    let health = world.healths.get_mut(&target)?;
+    health.current -= damage;
+    if let Some(combatant) = world.combatants.get_mut(&target) {
+        combatant.invulnerable = INVULNERABLE;
+        combatant.knockback = (direction.0 * KNOCKBACK, direction.1 * KNOCKBACK);
+        combatant.attacker = Some(attacker);
+    }
+    //weapons apply their effects to what they hit, apparel to whoever wears it
+    if kind == Attack::Melee {
+        status::trigger(world, attacker, Trigger::Contact, Some(target));
+    }
+    if kind != Attack::Magic {
+        durability::strike(world, attacker, target, kind == Attack::Melee);
+    }
+    status::trigger(world, target, Trigger::Hit, Some(attacker));
+    Some(Hit {
+        attacker,
+        target,
+        damage,
+        critical,
+    })
+}
+
+//hostile mobs that touch the player strike it
+pub fn contact(world: &mut World, affinities: &AffinityTable) -> Vec<Hit> {
+    let mut attacks = Vec::new();
+    for (attacker, ai) in world.ais.iter() {
+        if ai.friendly {
+            continue;
+        }
+        let (position, collider) = if let (Some(position), Some(collider)) = (world.positions.get(attacker), world.colliders.get(attacker)) {
+            (position, collider)
+        } else {
+            continue;
+        };
+        for (target, target_collider) in world.colliders.iter() {
+            if !is_hostile(world, *attacker, *target) {
+                continue;
+            }
+            if let Some(target_position) = world.positions.get(target) {
+                //solid colliders never overlap, so touching is checked with a little slack
+                let mut reach = collider.clone();
+                reach.w += CONTACT_SLACK * 2.0;
+                reach.h += CONTACT_SLACK * 2.0;
+                if reach.overlaps(position, target_collider, target_position) {
+                    attacks.push((*attacker, *target, (target_position.x - position.x, target_position.y - position.y)));
+                }
+            }
+        }
+    }
+    let mut hits = Vec::new();
+    for (attacker, target, aim) in attacks {
+        let ready = world.combatants.get(&attacker).map_or(false, |combatant| combatant.cooldown <= 0.0);
+        if !ready {
+            continue;
+        }
+        let length = (aim.0 * aim.0 + aim.1 * aim.1).sqrt().max(f32::EPSILON);
+        if let Some(hit) = strike(world, affinities, attacker, target, Attack::Melee, (aim.0 / length, aim.1 / length)) {
+            hits.push(hit);
+        }
+        let cooldown = world.sheets.get(&attacker).map_or(1.0, |sheet| 1.0 / sheet.derived.attack_speed.max(0.1));
+        if let Some(combatant) = world.combatants.get_mut(&attacker) {
+            combatant.cooldown = cooldown;
+        }
+    }
+    hits
+}
+
+//counts down cooldowns and invulnerability and slides knocked back entities, delta is in seconds
+pub fn update(world: &mut World, navigation: &Navigation, delta: f32) {
+    let ids = world.combatants.keys().cloned().collect::<Vec<_>>();
+    for id in ids {
+        let knockback = if let Some(combatant) = world.combatants.get_mut(&id) {
+            combatant.cooldown = (combatant.cooldown - delta).max(0.0);
+            combatant.invulnerable = (combatant.invulnerable - delta).max(0.0);
+            let knockback = combatant.knockback;
+            let drag = (-KNOCKBACK_DRAG * delta).exp();
+            combatant.knockback = (knockback.0 * drag, knockback.1 * drag);
+            knockback
+        } else {
+            continue;
+        };
+        if knockback.0.abs() < f32::EPSILON && knockback.1.abs() < f32::EPSILON {
+            continue;
+        }
+        //knockback never pushes through walls or other solid entities
+        let speed = (knockback.0 * knockback.0 + knockback.1 * knockback.1).sqrt();
+        magic::dash(world, navigation, id, (knockback.0 / speed, knockback.1 / speed), speed * delta);
+    }
+}
+
+//entities whose health ran out
+pub fn dead(world: &World) -> Vec<EntityId> {
+    world.healths.iter()
+        .filter(|(_, health)| health.current <= 0)
+        .map(|(id, _)| *id)
+        .collect()
+}
\ No newline at end of file
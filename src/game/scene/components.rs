@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::{data::{Stats, effect::Effect}, toml_loader::{TextureMap, CHUNK_SIZE, TILE_SIZE}, logger::error, Direction, Motion};
+
+use super::{animation::Animator, paper_doll::PaperDoll, world::EntityId, entity::Item, combat::Attack};
+
+//Where an entity is, in chunks, and which way it faces
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+    pub direction: Direction,
+}
+
+impl Position {
+    pub fn new(position: (f32, f32)) -> Self {
+        Self {
+            x: position.0,
+            y: position.1,
+            direction: Direction::Down,
+        }
+    }
+
+    pub fn get(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    //unit vector of the direction the entity faces
+    pub fn facing(&self) -> (f32, f32) {
+        match self.direction {
+            Direction::Down => (0.0, 1.0),
+            Direction::Left => (-1.0, 0.0),
+            Direction::Right => (1.0, 0.0),
+            Direction::Up => (0.0, -1.0),
+        }
+    }
+}
+
+//What an entity looks like, the animator is built from the texture map the first time it is drawn
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Sprite {
+    pub texture: String,
+    pub motion: Motion,
+    #[serde(skip)]
+    pub animator: Option<Animator>,
+    #[serde(skip)]
+    pub outfit: PaperDoll,
+}
+
+impl Sprite {
+    pub fn new(texture: String) -> Self {
+        Self {
+            texture,
+            ..Self::default()
+        }
+    }
+
+    //builds the animator, textures without an animation are drawn still
+    pub fn skin(&mut self, texture_map: &TextureMap) {
+        let rect = if let Some(rect) = texture_map.get_rect(&self.texture) {
+            rect
+        } else {
+            error!("Texture {} not found", self.texture);
+            //an empty animator stops the lookup from happening every frame
+            self.animator = Some(Animator::default());
+            return;
+        };
+        let atlas = texture_map.get_atlas_size();
+        self.animator = Some(if let Some(animation) = texture_map.get_animation(&self.texture) {
+            Animator::new(rect, atlas, animation)
+        } else {
+            Animator::still(rect, atlas)
+        });
+    }
+}
+
+//Hit points of an entity that can be hurt
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+}
+
+impl Health {
+    //keeps the same fraction of health when the maximum changes
+    pub fn set_max(&mut self, max: i32) {
+        if self.max == max {
+            return;
+        }
+        self.current = if self.max > 0 {
+            (self.current as i64 * max as i64 / self.max as i64) as i32
+        } else {
+            max
+        };
+        self.max = max;
+    }
+}
+
+//Mana an entity casts spells with, kept as a fraction so regeneration is smooth
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Mana {
+    pub current: f32,
+    pub max: i32,
+}
+
+impl Mana {
+    //keeps the same fraction of mana when the maximum changes
+    pub fn set_max(&mut self, max: i32) {
+        if self.max == max {
+            return;
+        }
+        self.current = if self.max > 0 {
+            self.current * max as f32 / self.max as f32
+        } else {
+            max as f32
+        };
+        self.max = max;
+    }
+
+    //takes mana away if there is enough of it, returns whether it was spent
+    pub fn spend(&mut self, amount: i32) -> bool {
+        if self.current < amount as f32 {
+            return false;
+        }
+        self.current -= amount as f32;
+        true
+    }
+
+    pub fn regenerate(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max as f32);
+    }
+}
+
+//What a mob is doing
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum AiState {
+    //standing around until the timer runs out
+    Idle,
+    //walking to a random tile near home
+    Wander,
+    //walking to the target
+    Chase(EntityId),
+    //close enough to hit the target
+    Attack(EntityId),
+    //running from the target
+    Flee(EntityId),
+    //walking after the leader, friendly mobs only
+    Follow(EntityId),
+}
+
+impl Default for AiState {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+//How a mob decides what to do
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Ai {
+    pub friendly: bool,
+    //where the mob was spawned, in chunks
+    pub home: (f32, f32),
+    pub state: AiState,
+    //tiles left to walk through, the next one is last
+    pub path: Vec<(i32, i32)>,
+    //seconds until the mob thinks about its path or idling again
+    pub timer: f32,
+    //entity a friendly mob walks after instead of wandering, such as the player escorting it
+    pub leader: Option<EntityId>,
+}
+
+impl Ai {
+    pub fn new(friendly: bool, home: (f32, f32)) -> Self {
+        Self {
+            friendly,
+            home,
+            ..Self::default()
+        }
+    }
+}
+
+//An effect applied to an entity, along with the stats it gives
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    //stats of the source, such as the item that applied the effect
+    pub stats: Stats,
+    //rolled between the min and max of the effect
+    pub multiplier: f32,
+    //seconds left
+    pub remaining: f32,
+    //seconds since the effect last ticked
+    pub timer: f32,
+    //ticks left for auto effects, which run until they are used up instead of for their duration
+    pub uses: Option<i32>,
+    //entity that applied the effect, damage over time is dealt by it
+    pub source: Option<EntityId>,
+    //fractions of a point healed and dealt that did not land yet, they carry over to the next tick
+    #[serde(default)]
+    pub heal_carry: f32,
+    #[serde(default)]
+    pub damage_carry: f32,
+}
+
+//Effects currently applied to an entity
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Effects {
+    pub active: Vec<ActiveEffect>,
+    //seconds until a consumable can be used again, keyed by item id since consumables of a kind share one
+    pub item_cooldowns: HashMap<String, f32>,
+    //seconds until a proximity item triggers again, keyed by item name
+    pub proximity_cooldowns: HashMap<String, f32>,
+}
+
+//Space an entity takes up, in texture pixels centered on its position
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Collider {
+    pub w: f32,
+    pub h: f32,
+    //solid entities block each other, others can be walked through
+    pub solid: bool,
+}
+
+impl Collider {
+    pub fn new(w: f32, h: f32, solid: bool) -> Self {
+        Self { w, h, solid }
+    }
+
+    //size in chunks
+    pub fn size(&self) -> (f32, f32) {
+        let chunk = CHUNK_SIZE * TILE_SIZE;
+        (self.w / chunk, self.h / chunk)
+    }
+
+    pub fn overlaps(&self, position: &Position, other: &Collider, other_position: &Position) -> bool {
+        let (w, h) = self.size();
+        let (other_w, other_h) = other.size();
+        (position.x - other_position.x).abs() * 2.0 < w + other_w
+            && (position.y - other_position.y).abs() * 2.0 < h + other_h
+    }
+}
+
+//Items lying on the ground waiting to be picked up
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pickup {
+    pub item: Item,
+    pub amount: i32,
+}
+
+impl Pickup {
+    pub fn new(item: Item, amount: i32) -> Self {
+        Self {
+            item,
+            amount,
+        }
+    }
+}
+
+//A projectile in the air
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Flight {
+    //entity that shot it, credited with whatever it hits
+    pub owner: EntityId,
+    //how the damage of the hit is worked out
+    pub kind: Attack,
+    //chunks per second
+    pub velocity: (f32, f32),
+    //seconds until it falls apart
+    pub lifetime: f32,
+    pub effect: Option<Effect>,
+}
+
+//Level of an entity, only characters gain experience and points
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Level {
+    pub level: i32,
+    //experience towards the next level
+    pub experience: i32,
+    //stat points that have not been spent yet
+    pub points: i32,
+}
+
+impl Level {
+    pub fn new(level: i32, experience: i32) -> Self {
+        Self {
+            level,
+            experience,
+            points: 0,
+        }
+    }
+}
+
+//Absorbs incoming magic until it runs out or wears off
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Shield {
+    //damage left to absorb
+    pub absorb: i32,
+    //seconds until it wears off
+    pub time: f32,
+}
+
+impl Shield {
+    pub fn new(absorb: i32, time: f32) -> Self {
+        Self {
+            absorb,
+            time,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,66 @@
+use crate::core::logger::debug;
+
+use super::{world::{World, EntityId}, entity::{Item, SlotRef}, combat::weapon_hand};
+
+//wears a piece of equipment down by one use
+fn wear(item: &mut Item) {
+    if !item.has_durability() || item.is_broken() {
+        return;
+    }
+    item.uses -= 1;
+    if item.is_broken() {
+        debug!("{} broke", item.name);
+    }
+}
+
+//the apparel of whoever took a hit loses durability, as does the weapon that landed it when it was swung
+pub fn strike(world: &mut World, attacker: EntityId, target: EntityId, swung: bool) {
+    if let Some(hand) = weapon_hand(world, attacker).filter(|_| swung) {
+        if let Some(item) = world.inventories.get_mut(&attacker).and_then(|inventory| inventory.holding.get_mut(&hand)).and_then(|slot| slot.item.as_mut()) {
+            wear(item);
+        }
+    }
+    if let Some(inventory) = world.inventories.get_mut(&target) {
+        for item in inventory.apparel.values_mut().filter_map(|slot| slot.item.as_mut()) {
+            wear(item);
+        }
+    }
+}
+
+//coins a repair costs, the value of the item for every use it is missing
+pub fn repair_cost(item: &Item) -> i32 {
+    if !item.has_durability() {
+        return 0;
+    }
+    let missing = (item.max_uses - item.uses).max(0) as f32 / item.max_uses as f32;
+    (item.value as f32 * missing).ceil() as i32
+}
+
+/**
+ * Pays for an item to get all of its durability back
+ * world: &mut World - The world the holder is in
+ * holder: EntityId - The entity paying, with the item in its inventory
+ * slot: &SlotRef - The slot the item is in
+ * returns: bool - Whether the item was repaired, false when it was not worn or could not be paid for
+ */
+pub fn repair(world: &mut World, holder: EntityId, slot: &SlotRef) -> bool {
+    let inventory = if let Some(inventory) = world.inventories.get_mut(&holder) {
+        inventory
+    } else {
+        return false;
+    };
+    let cost = if let Some(item) = inventory.get(slot).and_then(|slot| slot.item.as_ref()).filter(|item| item.has_durability() && item.uses < item.max_uses) {
+        repair_cost(item)
+    } else {
+        return false;
+    };
+    if !inventory.pay(cost) {
+        debug!("Repairing costs {} coins", cost);
+        return false;
+    }
+    if let Some(item) = inventory.get_mut(slot).and_then(|slot| slot.item.as_mut()) {
+        item.uses = item.max_uses;
+        debug!("Repaired {} for {} coins", item.name, cost);
+    }
+    true
+}
\ No newline at end of file
@@ -1,10 +1,22 @@
 use std::collections::HashMap;
 
-use ggez::graphics::Image;
+use rand::Rng;
 use serde::{Serialize, Deserialize};
+use serde_with::{serde_as, NoneAsEmptyString};
+use uuid::Uuid;
 
-use crate::core::{toml_loader::{Rect, Size}, data::{Stats, mob::{self,Range}, race::Race, class::Class, character, item, effect::Effect, Affinity}, functions::Vector2D, Direction};
+use crate::core::{toml_loader::{Rect, Size}, data::{self, Stats, mob::{self,Range}, race::Race, class::Class, character, item::{self, Kind, Rarity}, affix::{self, Affix}, effect::Effect, projectile, Affinity}, Direction};
 
+use super::{world::{World, EntityId}, components::{Position, Sprite, Health, Ai, Effects, Collider, Pickup, Level, Mana, Flight}, combat::{Combatant, Attack}, magic::Spell};
+
+//Size of a humanoid body in texture pixels
+const BODY: (f32, f32) = (16.0, 32.0);
+//Size of an item lying on the ground in texture pixels
+const ITEM_SIZE: f32 = 16.0;
+//Slots on the quickbar
+pub const QUICKBAR: usize = 4;
+
+//Templates that live entities are spawned from
 #[derive(Serialize, Deserialize, Clone, Debug)]
 
 pub enum Entity {
@@ -13,6 +25,17 @@ pub enum Entity {
     Mob(Mob),
 }
 
+impl Entity {
+    //position is in chunks
+    pub fn spawn(&self, world: &mut World, position: (f32, f32)) -> EntityId {
+        match self {
+            Entity::Character(character) => character.spawn(world, position),
+            Entity::Item(item) => item.spawn(world, position),
+            Entity::Mob(mob) => mob.spawn(world, position),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Slot {
     pub name: String,
@@ -20,136 +43,593 @@ pub struct Slot {
     pub amount: i32,
 }
 
+#[serde_as]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Inventory {
     pub size: Size,
-    pub data: HashMap<String, Item>,
     pub apparel: HashMap<String,Slot>,
     pub holding: HashMap<String,Slot>,
+    //items carried but not equipped, one slot for every cell of the size
+    #[serde(default)]
+    pub bag: Vec<Slot>,
+    //ids of the consumables on the quickbar, empty ones are saved as empty strings since toml has no none
+    #[serde_as(as = "Vec<NoneAsEmptyString>")]
+    #[serde(default)]
+    pub quickbar: Vec<Option<String>>,
+}
+
+//Where a slot of an inventory is
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SlotRef {
+    //index of the cell in the bag, row by row
+    Bag(usize),
+    Apparel(String),
+    Holding(String),
+}
+
+impl Inventory {
+    pub fn get(&self, slot: &SlotRef) -> Option<&Slot> {
+        match slot {
+            SlotRef::Bag(index) => self.bag.get(*index),
+            SlotRef::Apparel(name) => self.apparel.get(name),
+            SlotRef::Holding(name) => self.holding.get(name),
+        }
+    }
+
+    pub fn get_mut(&mut self, slot: &SlotRef) -> Option<&mut Slot> {
+        match slot {
+            SlotRef::Bag(index) => self.bag.get_mut(*index),
+            SlotRef::Apparel(name) => self.apparel.get_mut(name),
+            SlotRef::Holding(name) => self.holding.get_mut(name),
+        }
+    }
+
+    /**
+     * Whether an item is allowed in a slot, apparel only goes on the part of the body it is made for
+     * slot: &SlotRef - The slot the item would go in
+     * item: &Item - The item
+     * leaving: &SlotRef - The slot the item comes from, which is empty once it moves
+     * returns: bool - Whether the item fits
+     */
+    pub fn accepts(&self, slot: &SlotRef, item: &Item, leaving: &SlotRef) -> bool {
+        match slot {
+            SlotRef::Bag(_) => true,
+            SlotRef::Apparel(name) => item.kind == Kind::Apparel && item.slot == *name,
+            SlotRef::Holding(name) => {
+                if !matches!(item.kind, Kind::Weapon | Kind::Consumable) {
+                    return false;
+                }
+                //two handed weapons need every other hand to be free, and keep them free
+                self.holding.values()
+                    .filter(|hand| hand.name != *name && SlotRef::Holding(hand.name.clone()) != *leaving)
+                    .filter_map(|hand| hand.item.as_ref())
+                    .all(|held| !item.two_handed && !held.two_handed)
+            },
+        }
+    }
+
+    /**
+     * Moves items between two slots, merging stacks of the same item and swapping different ones
+     * from: &SlotRef - The slot to take the items from
+     * to: &SlotRef - The slot to put the items in
+     * amount: Option<i32> - How many to move into an empty slot or onto a stack, None moves all of them
+     * returns: bool - Whether anything moved
+     */
+    pub fn move_item(&mut self, from: &SlotRef, to: &SlotRef, amount: Option<i32>) -> bool {
+        if from == to {
+            return false;
+        }
+        let (source, available) = if let Some((Some(item), amount)) = self.get(from).map(|slot| (slot.item.clone(), slot.amount)) {
+            (item, amount.max(1))
+        } else {
+            return false;
+        };
+        let target = if let Some(slot) = self.get(to) {
+            (slot.item.clone(), slot.amount)
+        } else {
+            return false;
+        };
+        if !self.accepts(to, &source, from) {
+            return false;
+        }
+        let mut moving = amount.unwrap_or(available).clamp(1, available);
+        match target {
+            //stacks only fill up to the most the item stacks to, the rest stays behind
+            (Some(item), held) if item.stacks_with(&source) && source.stack > 1 => {
+                //equipment holds a single item, so nothing is added onto one that is already there
+                let most = if matches!(to, SlotRef::Bag(_)) { source.stack } else { 1 };
+                moving = moving.min(most - held.max(1));
+                if moving <= 0 {
+                    return false;
+                }
+                self.get_mut(to).unwrap().amount = held + moving;
+            },
+            //a different item can only swap when all of the source moves and the target fits where the source was
+            (Some(item), held) => {
+                //equipment only ever holds one item
+                let fits = (matches!(to, SlotRef::Bag(_)) || moving == 1) && (matches!(from, SlotRef::Bag(_)) || held <= 1);
+                if moving < available || !fits || !self.accepts(from, &item, to) {
+                    return false;
+                }
+                let slot = self.get_mut(from).unwrap();
+                slot.item = Some(item);
+                slot.amount = held;
+                let slot = self.get_mut(to).unwrap();
+                slot.item = Some(source);
+                slot.amount = moving;
+                return true;
+            },
+            (None, _) => {
+                //equipment holds a single item
+                if !matches!(to, SlotRef::Bag(_)) {
+                    moving = 1;
+                }
+                let slot = self.get_mut(to).unwrap();
+                slot.item = Some(source);
+                slot.amount = moving;
+            },
+        }
+        let slot = self.get_mut(from).unwrap();
+        slot.amount = available - moving;
+        if slot.amount <= 0 {
+            slot.item = None;
+            slot.amount = 0;
+        }
+        true
+    }
+
+    /**
+     * Puts items into the bag, filling stacks of the same item before taking empty slots
+     * item: &Item - The item to store
+     * amount: i32 - How many of the item to store
+     * returns: i32 - How many did not fit
+     */
+    pub fn store(&mut self, item: &Item, amount: i32) -> i32 {
+        let mut left = amount;
+        let stack = item.stack.max(1);
+        for slot in self.bag.iter_mut().filter(|slot| slot.item.as_ref().map_or(false, |held| held.stacks_with(item))) {
+            if left <= 0 {
+                break;
+            }
+            let amount = left.min(stack - slot.amount).max(0);
+            slot.amount += amount;
+            left -= amount;
+        }
+        let mut copy = item.clone();
+        for slot in self.bag.iter_mut().filter(|slot| slot.item.is_none()) {
+            if left <= 0 {
+                break;
+            }
+            let amount = left.min(stack);
+            slot.item = Some(copy.clone());
+            //every further slot holds another instance
+            copy.uuid = Uuid::new_v4();
+            slot.amount = amount;
+            left -= amount;
+        }
+        left
+    }
+
+    //slot holding an item with the id, hands are looked through before the bag
+    pub fn find(&self, id: &str) -> Option<SlotRef> {
+        let mut hands = self.holding.values().collect::<Vec<_>>();
+        hands.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(hand) = hands.into_iter().find(|slot| slot.item.as_ref().map_or(false, |item| item.id == id)) {
+            return Some(SlotRef::Holding(hand.name.clone()));
+        }
+        self.bag.iter()
+            .position(|slot| slot.item.as_ref().map_or(false, |item| item.id == id))
+            .map(SlotRef::Bag)
+    }
+
+    //how many of an item are carried
+    pub fn count(&self, id: &str) -> i32 {
+        self.bag.iter()
+            .chain(self.holding.values())
+            .filter(|slot| slot.item.as_ref().map_or(false, |item| item.id == id))
+            .map(|slot| slot.amount.max(1))
+            .sum()
+    }
+
+    /**
+     * Takes an item out of the bag and hands, emptying the slots it leaves
+     * id: &str - The data id of the item
+     * amount: i32 - How many to take at most
+     * returns: i32 - How many were taken
+     */
+    pub fn take(&mut self, id: &str, amount: i32) -> i32 {
+        let mut left = amount;
+        for slot in self.bag.iter_mut().chain(self.holding.values_mut()) {
+            if left <= 0 {
+                break;
+            }
+            if !slot.item.as_ref().map_or(false, |item| item.id == id) {
+                continue;
+            }
+            let taken = left.min(slot.amount.max(1));
+            slot.amount -= taken;
+            left -= taken;
+            if slot.amount <= 0 {
+                slot.item = None;
+                slot.amount = 0;
+            }
+        }
+        amount - left
+    }
+
+    /**
+     * Takes coins out of the bag
+     * amount: i32 - How much the coins have to be worth
+     * returns: bool - Whether there were enough, nothing is taken when there were not
+     */
+    pub fn pay(&mut self, amount: i32) -> bool {
+        let coins = |slot: &Slot| slot.item.as_ref().filter(|item| item.kind == Kind::Currency).map_or(0, |item| item.value.max(1) * slot.amount);
+        if self.bag.iter().map(coins).sum::<i32>() < amount {
+            return false;
+        }
+        let mut left = amount;
+        for slot in self.bag.iter_mut() {
+            let value = if let Some(item) = slot.item.as_ref().filter(|item| item.kind == Kind::Currency) {
+                item.value.max(1)
+            } else {
+                continue;
+            };
+            if left <= 0 {
+                break;
+            }
+            //coins are not split, paying with a bigger coin loses the difference
+            let taken = ((left + value - 1) / value).min(slot.amount);
+            slot.amount -= taken;
+            left -= taken * value;
+            if slot.amount <= 0 {
+                slot.item = None;
+                slot.amount = 0;
+            }
+        }
+        true
+    }
+
+    //weight of everything in the inventory
+    pub fn weight(&self) -> f32 {
+        self.bag.iter()
+            .chain(self.apparel.values())
+            .chain(self.holding.values())
+            .filter_map(|slot| slot.item.as_ref().map(|item| item.weight * slot.amount.max(1) as f32))
+            .sum()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Character {
-    pub current_pos: Vector2D,
     pub store: String,
     pub race: Race,
     pub class: Class,
     pub name: String,
     pub level: i32,
     pub experience: i32,
+    //stat points earned but not spent yet
+    #[serde(default)]
+    pub points: i32,
     pub affinity: Affinity,
     pub stats: Stats,
     pub inventory: Inventory,
-    #[serde(skip)]
-    pub texture: Vec<Image>,
-    #[serde(skip)]
-    pub position: (i32, i32),
-    #[serde(skip)]
-    pub direction: Direction,
 }
 
 impl Character {
-    pub fn new(character: character::Character, items: HashMap<String, Item>, classes: HashMap<String, Class>, races: HashMap<String, Race>, position: (i32,i32)) -> Self {
-        let mut apparel = HashMap::new();
-        for slot in character.inventory.apparel {
-            let item = if let Some(val) = items.get(&slot.item){
-                Some(val.clone())
-            } else {
-                None
-            };
-            let slot = Slot {
-                name: slot.name,
-                item,
-                amount: slot.amount,
-            };
-            apparel.insert(slot.name.clone(), slot);
-        }
-        let mut holding = HashMap::new();
-        for slot in character.inventory.holding {
-            let item = if let Some(val) = items.get(&slot.item) {
-                Some(val.clone())
-            } else {
-                None
-            };
-            let slot = Slot {
-                name: slot.name,
-                item,
-                amount: slot.amount,
-            };
-            holding.insert(slot.name.clone(), slot);
+    pub fn new(character: character::Character, items: HashMap<String, Item>, affixes: &[Affix], classes: HashMap<String, Class>, races: HashMap<String, Race>) -> Self {
+        //every item the character starts with is its own instance
+        let equip = |slots: Vec<data::Slot>| -> HashMap<String, Slot> {
+            slots.into_iter().map(|slot| {
+                let slot = Slot {
+                    item: items.get(&slot.item).map(|item| item.instance(affixes)),
+                    name: slot.name,
+                    amount: slot.amount,
+                };
+                (slot.name.clone(), slot)
+            }).collect()
+        };
+        let apparel = equip(character.inventory.apparel);
+        let holding = equip(character.inventory.holding);
+        let mut inventory = Inventory {
+            bag: (0..character.inventory.size.w * character.inventory.size.h).map(|index| Slot {
+                name: index.to_string(),
+                item: None,
+                amount: 0,
+            }).collect(),
+            quickbar: vec![None; QUICKBAR],
+            size: character.inventory.size,
+            apparel,
+            holding,
+        };
+        for id in &character.inventory.data {
+            if let Some(item) = items.get(id) {
+                inventory.store(&item.instance(affixes), 1);
+            }
         }
         Self {
-            current_pos: Vector2D{ x: 0.0, y: 0.0},
             store: character.store,
             race: races.get(&character.race.clone()).unwrap().clone(),
             class: classes.get(&character.class.clone()).unwrap().clone(),
             name: character.name,
             level: character.level,
             experience: character.experience,
+            points: 0,
             affinity: character.affinity,
             stats: character.stats,
-            inventory: Inventory {
-                size: character.inventory.size,
-                data: items,
-                apparel,
-                holding,
-            },
-            texture: Vec::new(),
-            position,
-            direction: Direction::Down,
+            inventory,
         }
     }
 
-    pub fn push_texture(&mut self, texture: Image) {
-        self.texture.push(texture);
+    //position is in chunks
+    pub fn spawn(&self, world: &mut World, position: (f32, f32)) -> EntityId {
+        let id = world.spawn();
+        world.names.insert(id, self.name.clone());
+        world.positions.insert(id, Position::new(position));
+        world.sprites.insert(id, Sprite::new(self.race.texture.clone()));
+        world.stats.insert(id, self.stats.clone());
+        world.races.insert(id, self.race.clone());
+        world.classes.insert(id, self.class.clone());
+        //characters without an affinity of their own take the one of their race
+        let affinity = if self.affinity.is_none() { &self.race.affinity } else { &self.affinity };
+        world.affinities.insert(id, affinity.clone());
+        world.healths.insert(id, Health::default());
+        world.manas.insert(id, Mana::default());
+        world.inventories.insert(id, self.inventory.clone());
+        world.levels.insert(id, Level { points: self.points, ..Level::new(self.level, self.experience) });
+        world.spells.insert(id, Spell::default());
+        world.effects.insert(id, Effects::default());
+        world.colliders.insert(id, Collider::new(BODY.0, BODY.1, true));
+        world.combatants.insert(id, Combatant::default());
+        id
     }
 
-    pub fn get_texture(&self, direction: Direction) -> &Image {
-        if direction == Direction::Down {
-            return &self.texture[0];
-        } else if direction == Direction::Left {
-            return &self.texture[1];
-        } else if direction == Direction::Right {
-            return &self.texture[2];
+    //takes back what the spawned character gained, the world is where it changes while playing
+    pub fn sync(&mut self, world: &World, id: EntityId) {
+        if let Some(inventory) = world.inventories.get(&id) {
+            self.inventory = inventory.clone();
+        }
+        if let Some(stats) = world.stats.get(&id) {
+            self.stats = stats.clone();
+        }
+        if let Some(level) = world.levels.get(&id) {
+            self.level = level.level;
+            self.experience = level.experience;
+            self.points = level.points;
         }
-        &self.texture[3]
-    }
-
-    pub fn move_vert(&mut self, step: i32) {
-        self.position.1 += step;
-    }
-
-    pub fn move_hor(&mut self, step: i32) {
-        self.position.0 += step;
-    }
-
-    #[allow(dead_code)]
-    pub fn shape(&self) -> (u32,u32) {
-        (self.texture[0].width(), self.texture[0].height())
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Item {
+    //id of the definition in the data files
+    pub id: String,
+    //unique to this copy of the item, nil for templates that were never handed out
+    #[serde(default)]
+    pub uuid: Uuid,
     pub name: String,
     pub texture: Rect,
+    pub texture_id: String,
     pub stats: Stats,
     pub effect: Option<Effect>,
+    //uses left, which is the durability of equipment
     pub uses: i32,
+    //uses the item started with
+    pub max_uses: i32,
     pub auto: bool,
+    pub kind: Kind,
+    pub slot: String,
+    pub two_handed: bool,
+    pub stack: i32,
+    pub weight: f32,
+    pub rarity: Rarity,
+    pub value: i32,
+    pub cooldown: f32,
+    //stats rolled when the item was handed out
+    #[serde(default)]
+    pub affixes: Vec<RolledAffix>,
 }
 
 impl Item {
     pub fn new(item: item::Item, effect: Option<Effect>, texture: Rect) -> Self {
         Self {
+            id: item.id,
+            uuid: Uuid::nil(),
             name: item.name,
             texture,
+            texture_id: item.texture,
             stats: item.stats,
             effect,
             uses: item.uses,
+            max_uses: item.uses,
             auto: item.auto,
+            kind: item.kind,
+            slot: item.slot,
+            two_handed: item.two_handed,
+            stack: item.stack,
+            weight: item.weight,
+            rarity: item.rarity,
+            value: item.value,
+            cooldown: item.cooldown,
+            affixes: Vec::new(),
         }
     }
+
+    /**
+     * Makes a copy of the item with its own uuid, rolling at most one prefix and one suffix
+     * affixes: &[Affix] - The affixes that can roll, an empty list gives a plain copy
+     * returns: Item - The new instance
+     */
+    pub fn instance(&self, affixes: &[Affix]) -> Item {
+        let mut rng = rand::thread_rng();
+        let mut item = self.clone();
+        item.uuid = Uuid::new_v4();
+        item.affixes = Vec::new();
+        for position in [affix::Position::Prefix, affix::Position::Suffix] {
+            let rolled = affixes.iter()
+                .filter(|affix| affix.position == position && affix.kinds.contains(&self.kind))
+                .find(|affix| rng.gen::<f32>() < affix.chance);
+            if let Some(affix) = rolled {
+                let multiplier = rng.gen_range(affix.min.min(affix.max)..=affix.max.max(affix.min));
+                item.affixes.push(RolledAffix {
+                    id: affix.id.clone(),
+                    name: affix.name.clone(),
+                    position,
+                    stats: affix.stats.scaled(multiplier),
+                });
+            }
+        }
+        item
+    }
+
+    //items only share a stack when they are made from the same definition and rolled the same affixes
+    pub fn stacks_with(&self, other: &Item) -> bool {
+        self.id == other.id && self.affixes == other.affixes
+    }
+
+    //stats of the definition with every rolled affix added
+    pub fn total_stats(&self) -> Stats {
+        let mut stats = self.stats.clone();
+        for affix in &self.affixes {
+            stats += &affix.stats;
+        }
+        stats
+    }
+
+    //name with the prefixes before it and the suffixes after it
+    pub fn display_name(&self) -> String {
+        let mut name = self.name.clone();
+        for rolled in &self.affixes {
+            name = match rolled.position {
+                affix::Position::Prefix => format!("{} {}", rolled.name, name),
+                affix::Position::Suffix => format!("{} {}", name, rolled.name),
+            };
+        }
+        name
+    }
+
+    //equipment wears down instead of being used up
+    pub fn has_durability(&self) -> bool {
+        matches!(self.kind, Kind::Apparel | Kind::Weapon) && self.max_uses > 0
+    }
+
+    //broken equipment stays equipped but gives nothing
+    pub fn is_broken(&self) -> bool {
+        self.has_durability() && self.uses <= 0
+    }
+
+    pub fn condition(&self) -> Condition {
+        if !self.has_durability() {
+            return Condition::Pristine;
+        }
+        let left = self.uses as f32 / self.max_uses as f32;
+        if left <= 0.0 {
+            Condition::Broken
+        } else if left <= 0.25 {
+            Condition::Damaged
+        } else if left <= 0.75 {
+            Condition::Worn
+        } else {
+            Condition::Pristine
+        }
+    }
+
+    //drops a single item on the ground, position is in chunks
+    pub fn spawn(&self, world: &mut World, position: (f32, f32)) -> EntityId {
+        self.instance(&[]).drop(world, position, 1)
+    }
+
+    //drops a pile of the item on the ground that can be picked up, position is in chunks
+    pub fn drop(&self, world: &mut World, position: (f32, f32), amount: i32) -> EntityId {
+        let id = world.spawn();
+        world.names.insert(id, self.display_name());
+        world.positions.insert(id, Position::new(position));
+        world.sprites.insert(id, Sprite::new(self.texture_id.clone()));
+        world.colliders.insert(id, Collider::new(ITEM_SIZE, ITEM_SIZE, false));
+        world.pickups.insert(id, Pickup::new(self.clone(), amount));
+        id
+    }
+}
+
+//Template projectiles are shot from
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Projectile {
+    pub id: String,
+    pub name: String,
+    pub texture_id: String,
+    //chunks per second
+    pub speed: f32,
+    //seconds
+    pub lifetime: f32,
+    //texture pixels
+    pub size: f32,
+    pub effect: Option<Effect>,
+}
+
+impl Projectile {
+    pub fn new(projectile: projectile::Projectile, effect: Option<Effect>) -> Self {
+        Self {
+            id: projectile.id,
+            name: projectile.name,
+            texture_id: projectile.texture,
+            speed: projectile.speed,
+            lifetime: projectile.lifetime,
+            size: projectile.size,
+            effect,
+        }
+    }
+
+    /**
+     * Shoots the projectile from a position
+     * world: &mut World - The world to shoot into
+     * owner: EntityId - The entity shooting, credited with whatever it hits
+     * kind: Attack - How the damage of a hit is worked out
+     * origin: (f32, f32) - Where it starts in chunks
+     * aim: (f32, f32) - The normalized direction it flies in
+     * reach: f32 - Chunks it flies at most, it falls apart sooner if its lifetime runs out first
+     * returns: EntityId - The projectile
+     */
+    pub fn spawn(&self, world: &mut World, owner: EntityId, kind: Attack, origin: (f32, f32), aim: (f32, f32), reach: f32) -> EntityId {
+        let id = world.spawn();
+        let mut position = Position::new(origin);
+        //sprites only have four sides, the one closest to the aim is used
+        position.direction = if aim.0.abs() > aim.1.abs() {
+            if aim.0 > 0.0 { Direction::Right } else { Direction::Left }
+        } else if aim.1 > 0.0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        };
+        world.names.insert(id, self.name.clone());
+        world.positions.insert(id, position);
+        world.sprites.insert(id, Sprite::new(self.texture_id.clone()));
+        world.colliders.insert(id, Collider::new(self.size, self.size, false));
+        world.projectiles.insert(id, Flight {
+            owner,
+            kind,
+            velocity: (aim.0 * self.speed, aim.1 * self.speed),
+            lifetime: self.lifetime.min(reach / self.speed.max(f32::EPSILON)),
+            effect: self.effect.clone(),
+        });
+        id
+    }
+}
+
+//An affix as it rolled on an item
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RolledAffix {
+    pub id: String,
+    pub name: String,
+    pub position: affix::Position,
+    pub stats: Stats,
+}
+
+//How worn down a piece of equipment is
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Condition {
+    Pristine,
+    Worn,
+    Damaged,
+    Broken,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -158,16 +638,35 @@ pub enum DropType {
     Range(Range),
 }
 
+impl DropType {
+    pub fn roll(&self) -> i32 {
+        match self {
+            DropType::Fixed(amount) => *amount,
+            DropType::Range(range) => rand::thread_rng().gen_range(range.min.min(range.max)..=range.max.max(range.min)),
+        }
+    }
+}
+
+//An item a mob may drop, chance is from 0 to 1 before luck
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ItemDrop {
+    pub item: Item,
+    pub chance: f32,
+    pub amount: DropType,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum Drops {
-    Item(Item),
+    Item(ItemDrop),
     Exp(Range)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Mob {
+    pub id: String,
     pub name: String,
     pub texture: Rect,
+    pub texture_id: String,
     pub level: i32,
     pub stats: Stats,
     pub friendly: bool,
@@ -180,9 +679,17 @@ impl Mob {
         let mut drops = Vec::new();
         for drop in mob.drops {
             match drop {
-                mob::Drops::Item(item) => {
-                    let item = items.get(&item.id).unwrap().clone();
-                    drops.push(Drops::Item(item));
+                mob::Drops::Item(drop) => {
+                    let item = items.get(&drop.id).unwrap().clone();
+                    let amount = match drop.amount {
+                        mob::DropType::Fixed(amount) => DropType::Fixed(amount),
+                        mob::DropType::Range(range) => DropType::Range(range),
+                    };
+                    drops.push(Drops::Item(ItemDrop {
+                        item,
+                        chance: drop.chance,
+                        amount,
+                    }));
                 },
                 mob::Drops::Exp(range) => {
                     drops.push(Drops::Exp(range));
@@ -190,8 +697,10 @@ impl Mob {
             }
         }
         Self {
+            id: mob.id,
             name: mob.name,
             texture,
+            texture_id: mob.texture,
             level: mob.level,
             stats: mob.stats,
             friendly: mob.friendly,
@@ -199,4 +708,163 @@ impl Mob {
             drops,
         }
     }
+
+    //position is in chunks
+    pub fn spawn(&self, world: &mut World, position: (f32, f32)) -> EntityId {
+        let id = world.spawn();
+        world.names.insert(id, self.name.clone());
+        world.templates.insert(id, self.id.clone());
+        world.positions.insert(id, Position::new(position));
+        world.sprites.insert(id, Sprite::new(self.texture_id.clone()));
+        world.stats.insert(id, self.stats.clone());
+        world.affinities.insert(id, self.affinity.clone());
+        world.healths.insert(id, Health::default());
+        world.ais.insert(id, Ai::new(self.friendly, position));
+        world.drops.insert(id, self.drops.clone());
+        world.levels.insert(id, Level::new(self.level, 0));
+        world.effects.insert(id, Effects::default());
+        world.colliders.insert(id, Collider::new(BODY.0, BODY.1, true));
+        world.combatants.insert(id, Combatant::default());
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, kind: Kind, stack: i32, value: i32) -> Item {
+        let definition = item::Item {
+            id: id.to_string(),
+            name: id.to_string(),
+            texture: String::new(),
+            stats: Stats::default(),
+            effect: String::new(),
+            uses: -1,
+            auto: false,
+            kind,
+            slot: String::new(),
+            two_handed: false,
+            stack,
+            weight: 1.0,
+            rarity: Rarity::default(),
+            value,
+            cooldown: 0.0,
+        };
+        Item::new(definition, None, Rect { x: 0.0, y: 0.0, w: 0.0, h: 0.0 }).instance(&[])
+    }
+
+    fn slot(name: &str) -> (String, Slot) {
+        (name.to_string(), Slot { name: name.to_string(), item: None, amount: 0 })
+    }
+
+    //an empty bag of some cells, with a head and two hands
+    fn inventory(cells: u32) -> Inventory {
+        Inventory {
+            size: Size { w: cells, h: 1 },
+            apparel: HashMap::from([slot("head")]),
+            holding: HashMap::from([slot("left"), slot("right")]),
+            bag: (0..cells).map(|index| slot(&index.to_string()).1).collect(),
+            quickbar: vec![None; QUICKBAR],
+        }
+    }
+
+    fn amounts(inventory: &Inventory) -> Vec<i32> {
+        inventory.bag.iter().map(|slot| slot.amount).collect()
+    }
+
+    #[test]
+    fn store_fills_stacks_before_empty_slots() {
+        let mut inventory = inventory(3);
+        let potion = item("potion", Kind::Consumable, 5, 0);
+        assert_eq!(inventory.store(&potion, 7), 0);
+        assert_eq!(amounts(&inventory), vec![5, 2, 0]);
+        //every slot holds its own instance
+        assert_ne!(inventory.bag[0].item.as_ref().unwrap().uuid, inventory.bag[1].item.as_ref().unwrap().uuid);
+        assert_eq!(inventory.store(&potion.instance(&[]), 2), 0);
+        assert_eq!(amounts(&inventory), vec![5, 4, 0]);
+    }
+
+    #[test]
+    fn store_returns_what_does_not_fit() {
+        let mut inventory = inventory(2);
+        assert_eq!(inventory.store(&item("potion", Kind::Consumable, 5, 0), 12), 2);
+        assert_eq!(amounts(&inventory), vec![5, 5]);
+    }
+
+    #[test]
+    fn store_keeps_different_affixes_apart() {
+        let mut inventory = inventory(2);
+        let plain = item("ring", Kind::Misc, 5, 0);
+        let mut rolled = plain.clone();
+        rolled.affixes.push(RolledAffix { id: "affix.keen".to_string(), name: "Keen".to_string(), position: affix::Position::Prefix, stats: Stats::default() });
+        inventory.store(&plain, 1);
+        inventory.store(&rolled, 1);
+        assert_eq!(amounts(&inventory), vec![1, 1]);
+    }
+
+    #[test]
+    fn move_item_merges_up_to_the_stack() {
+        let mut inventory = inventory(2);
+        let potion = item("potion", Kind::Consumable, 5, 0);
+        inventory.bag[0] = Slot { name: "0".to_string(), item: Some(potion.clone()), amount: 4 };
+        inventory.bag[1] = Slot { name: "1".to_string(), item: Some(potion), amount: 3 };
+        assert!(inventory.move_item(&SlotRef::Bag(0), &SlotRef::Bag(1), None));
+        assert_eq!(amounts(&inventory), vec![2, 5]);
+        assert!(!inventory.move_item(&SlotRef::Bag(0), &SlotRef::Bag(1), None));
+    }
+
+    #[test]
+    fn move_item_swaps_different_items() {
+        let mut inventory = inventory(2);
+        inventory.store(&item("potion", Kind::Consumable, 5, 0), 2);
+        inventory.store(&item("bone", Kind::Misc, 5, 0), 1);
+        assert!(inventory.move_item(&SlotRef::Bag(0), &SlotRef::Bag(1), None));
+        assert_eq!(inventory.bag[0].item.as_ref().unwrap().id, "bone");
+        assert_eq!(inventory.bag[1].item.as_ref().unwrap().id, "potion");
+        assert_eq!(amounts(&inventory), vec![1, 2]);
+    }
+
+    #[test]
+    fn move_item_puts_one_item_in_a_hand() {
+        let mut inventory = inventory(1);
+        inventory.store(&item("potion", Kind::Consumable, 5, 0), 4);
+        assert!(inventory.move_item(&SlotRef::Bag(0), &SlotRef::Holding("left".to_string()), None));
+        assert_eq!(inventory.holding["left"].amount, 1);
+        //nothing is stacked onto the one already held
+        assert!(!inventory.move_item(&SlotRef::Bag(0), &SlotRef::Holding("left".to_string()), None));
+        assert_eq!(amounts(&inventory), vec![3]);
+        //only apparel is worn
+        assert!(!inventory.move_item(&SlotRef::Bag(0), &SlotRef::Apparel("head".to_string()), None));
+    }
+
+    #[test]
+    fn take_empties_the_slots_it_takes_from() {
+        let mut inventory = inventory(3);
+        let potion = item("potion", Kind::Consumable, 2, 0);
+        inventory.store(&potion, 3);
+        inventory.store(&item("bone", Kind::Misc, 5, 0), 1);
+        assert_eq!(inventory.take("potion", 2), 2);
+        assert_eq!(amounts(&inventory), vec![0, 1, 1]);
+        assert!(inventory.bag[0].item.is_none());
+        //only what is carried can be taken
+        assert_eq!(inventory.take("potion", 5), 1);
+        assert_eq!(inventory.count("potion"), 0);
+        assert_eq!(inventory.count("bone"), 1);
+    }
+
+    #[test]
+    fn pay_takes_coins_only_when_there_are_enough() {
+        let mut inventory = inventory(3);
+        inventory.store(&item("copper", Kind::Currency, 100, 1), 3);
+        inventory.store(&item("gold", Kind::Currency, 100, 10), 2);
+        assert!(!inventory.pay(24));
+        assert_eq!(amounts(&inventory), vec![3, 2, 0]);
+        assert!(inventory.pay(12));
+        //coppers go first, then a whole gold coin covers the rest
+        assert_eq!(amounts(&inventory), vec![0, 1, 0]);
+        assert!(inventory.pay(10));
+        assert!(inventory.bag.iter().all(|slot| slot.item.is_none()));
+        assert!(!inventory.pay(1));
+    }
 }
\ No newline at end of file
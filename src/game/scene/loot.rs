@@ -0,0 +1,86 @@
+use rand::Rng;
+
+use crate::core::data::affix::Affix;
+
+use super::{world::{World, EntityId}, entity::{Drops, Item}};
+
+//Chunks around the dead entity that its drops are scattered within
+const SCATTER: f32 = 0.1;
+//Chunks away from the holder that items can be picked up from
+const PICKUP_REACH: f32 = 0.2;
+
+/**
+ * Rolls the items of a drop table
+ * drops: &[Drops] - The drop table
+ * luck: f32 - Multiplies the chance of every item, from the loot of the derived stats
+ * returns: Vec<(Item, i32)> - The items that dropped and how many of each
+ */
+pub fn roll(drops: &[Drops], luck: f32) -> Vec<(Item, i32)> {
+    let mut rng = rand::thread_rng();
+    drops.iter()
+        .filter_map(|drop| match drop {
+            Drops::Item(drop) => Some(drop),
+            Drops::Exp(_) => None,
+        })
+        .filter(|drop| rng.gen::<f32>() < drop.chance * luck)
+        .map(|drop| (drop.item.clone(), drop.amount.roll()))
+        .filter(|(_, amount)| *amount > 0)
+        .collect()
+}
+
+//rolls the drop table of a dead entity and scatters new instances of the items around where it died, luck comes from the killer
+pub fn scatter(world: &mut World, affixes: &[Affix], id: EntityId) {
+    let (origin, drops) = if let (Some(position), Some(drops)) = (world.positions.get(&id), world.drops.get(&id)) {
+        (position.get(), drops.clone())
+    } else {
+        return;
+    };
+    let luck = world.combatants.get(&id)
+        .and_then(|combatant| combatant.attacker)
+        .and_then(|attacker| world.sheets.get(&attacker))
+        .map_or(1.0, |sheet| sheet.derived.loot);
+    let mut rng = rand::thread_rng();
+    for (item, amount) in roll(&drops, luck) {
+        let position = (origin.0 + rng.gen_range(-SCATTER..=SCATTER), origin.1 + rng.gen_range(-SCATTER..=SCATTER));
+        item.instance(affixes).drop(world, position, amount);
+    }
+}
+
+/**
+ * Moves the items on the ground near an entity into its bag
+ * world: &mut World - The world the holder is in
+ * holder: EntityId - The entity picking the items up
+ * returns: Vec<(Item, i32)> - The items picked up and how many of each
+ */
+pub fn pick_up(world: &mut World, holder: EntityId) -> Vec<(Item, i32)> {
+    let origin = if let Some(position) = world.positions.get(&holder) {
+        position.get()
+    } else {
+        return Vec::new();
+    };
+    let nearby = world.pickups.keys()
+        .filter(|id| world.positions.get(id).map_or(false, |position| {
+            let (x, y) = (position.x - origin.0, position.y - origin.1);
+            (x * x + y * y).sqrt() <= PICKUP_REACH
+        }))
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut picked = Vec::new();
+    for id in nearby {
+        let (inventory, pickup) = if let (Some(inventory), Some(pickup)) = (world.inventories.get_mut(&holder), world.pickups.get_mut(&id)) {
+            (inventory, pickup)
+        } else {
+            continue;
+        };
+        let left = inventory.store(&pickup.item, pickup.amount);
+        if left < pickup.amount {
+            picked.push((pickup.item.clone(), pickup.amount - left));
+        }
+        //whatever did not fit stays on the ground
+        pickup.amount = left;
+        if left <= 0 {
+            world.despawn(id);
+        }
+    }
+    picked
+}
\ No newline at end of file
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::{data::rune::{Rune, Action}, toml_loader::CHUNK_SIZE, logger::debug};
+
+use super::{world::{World, EntityId}, components::Shield, combat::Attack, entity::Projectile, navigation::{Navigation, to_tile}};
+
+//Chunks a forward rune moves the caster
+const FORWARD_STEP: f32 = 0.2;
+//Chunks a bolt flies when no range rune came before it
+const BOLT_REACH: f32 = 0.5;
+//Magic a shield absorbs before intelligence
+const SHIELD_BASE: i32 = 5;
+//Seconds a shield lasts
+const SHIELD_TIME: f32 = 5.0;
+//Seconds every rune takes to cast before intelligence
+const CAST_TIME: f32 = 0.25;
+
+//A rune placed in a spell, with the number it was given
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Glyph {
+    pub rune: String,
+    pub value: Option<i32>,
+}
+
+//Runes chained together, cast one after the other
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Spell {
+    pub glyphs: Vec<Glyph>,
+}
+
+impl Spell {
+    /**
+     * Adds a rune to the end of the spell
+     * rune: &Rune - The rune to add
+     * value: Option<i32> - The number the rune takes, ignored by runes that do not take one
+     * returns: bool - Whether the rune was added, false when it needs a number above 0 and did not get one
+     */
+    pub fn push(&mut self, rune: &Rune, value: Option<i32>) -> bool {
+        let value = if rune.takes_value() {
+            if let Some(value) = value.filter(|value| *value > 0) {
+                Some(value)
+            } else {
+                return false;
+            }
+        } else {
+            None
+        };
+        self.glyphs.push(Glyph { rune: rune.id.clone(), value });
+        true
+    }
+
+    pub fn pop(&mut self) {
+        self.glyphs.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+    }
+
+    //mana the whole spell drains, runes that are not known cost nothing
+    pub fn cost(&self, runes: &HashMap<String, Rune>) -> i32 {
+        self.glyphs.iter()
+            .filter_map(|glyph| runes.get(&glyph.rune).map(|rune| rune.cost(glyph.value)))
+            .sum()
+    }
+}
+
+//A spell that goes off once its time runs out
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Casting {
+    pub spell: Spell,
+    //normalized direction the spell was aimed in
+    pub aim: (f32, f32),
+    //seconds until it goes off
+    pub time: f32,
+    //seconds it took from the start
+    pub total: f32,
+}
+
+//Why a spell could not be cast
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fizzle {
+    //already casting or without the components to cast
+    NotReady,
+    //no runes in the spell or no direction to cast it in
+    Empty,
+    //mana the spell costs and how much the caster has
+    Mana(i32, i32),
+}
+
+//moves an entity along a normalized direction a tile at a time, stopping at the last point before whatever blocks it
+pub fn dash(world: &mut World, navigation: &Navigation, id: EntityId, direction: (f32, f32), distance: f32) {
+    let step = 1.0 / CHUNK_SIZE;
+    let mut travelled = 0.0;
+    while travelled < distance {
+        let length = step.min(distance - travelled);
+        let mut moved = if let Some(position) = world.positions.get(&id) {
+            position.clone()
+        } else {
+            return;
+        };
+        moved.x += direction.0 * length;
+        moved.y += direction.1 * length;
+        if !navigation.is_walkable(to_tile(moved.get())) || world.is_blocked(id, &moved) {
+            return;
+        }
+        world.positions.insert(id, moved);
+        travelled += length;
+    }
+}
+
+/**
+ * Starts casting the spell of an entity, paying its mana up front
+ * world: &mut World - The world the caster is in
+ * runes: &HashMap<String, Rune> - Every rune, keyed by id
+ * caster: EntityId - The entity casting its spell
+ * aim: (f32, f32) - The direction to cast in, does not need to be normalized
+ * returns: Result<f32, Fizzle> - Seconds until the spell goes off, or why it could not be cast
+ */
+pub fn cast(world: &mut World, runes: &HashMap<String, Rune>, caster: EntityId, aim: (f32, f32)) -> Result<f32, Fizzle> {
+    if world.castings.contains_key(&caster) {
+        return Err(Fizzle::NotReady);
+    }
+    let (spell, sheet, mana) = if let (Some(spell), Some(sheet), Some(mana)) = (world.spells.get(&caster), world.sheets.get(&caster), world.manas.get_mut(&caster)) {
+        (spell, sheet, mana)
+    } else {
+        return Err(Fizzle::NotReady);
+    };
+    let length = (aim.0 * aim.0 + aim.1 * aim.1).sqrt();
+    if spell.glyphs.is_empty() || length == 0.0 {
+        return Err(Fizzle::Empty);
+    }
+    let cost = spell.cost(runes);
+    if !mana.spend(cost) {
+        return Err(Fizzle::Mana(cost, mana.current as i32));
+    }
+    //intelligence speeds every rune up
+    let time = CAST_TIME * spell.glyphs.len() as f32 / sheet.derived.cast_speed.max(0.1);
+    let casting = Casting {
+        spell: spell.clone(),
+        aim: (aim.0 / length, aim.1 / length),
+        time,
+        total: time,
+    };
+    world.castings.insert(caster, casting);
+    Ok(time)
+}
+
+//runs the runes of a spell in order, bolts are shot as projectiles
+fn release(world: &mut World, navigation: &Navigation, runes: &HashMap<String, Rune>, projectiles: &HashMap<String, Projectile>, caster: EntityId, spell: &Spell, aim: (f32, f32)) {
+    let shield = SHIELD_BASE + world.sheets.get(&caster).map_or(0, |sheet| sheet.total.int.max(0));
+    let mut reach = BOLT_REACH;
+    for glyph in &spell.glyphs {
+        let rune = if let Some(rune) = runes.get(&glyph.rune) {
+            rune
+        } else {
+            debug!("Unknown rune {}", glyph.rune);
+            continue;
+        };
+        match rune.todo {
+            Action::Forward => dash(world, navigation, caster, aim, FORWARD_STEP),
+            Action::Range => reach = glyph.value.unwrap_or(0).max(0) as f32 / CHUNK_SIZE,
+            Action::Bolt => {
+                let (origin, projectile) = if let (Some(position), Some(projectile)) = (world.positions.get(&caster), projectiles.get(&rune.projectile)) {
+                    (position.get(), projectile)
+                } else {
+                    debug!("{} has nothing to shoot", rune.id);
+                    continue;
+                };
+                projectile.spawn(world, caster, Attack::Magic, origin, aim, reach);
+            },
+            Action::Shield => {
+                world.shields.insert(caster, Shield::new(shield, SHIELD_TIME));
+            },
+        }
+    }
+}
+
+/**
+ * Regenerates mana, wears shields off and sets off spells that are done casting
+ * world: &mut World - The world to update
+ * navigation: &Navigation - The tiles that stop casters from moving
+ * runes: &HashMap<String, Rune> - Every rune, keyed by id
+ * projectiles: &HashMap<String, Projectile> - What bolts shoot, keyed by id
+ * delta: f32 - Seconds since the last update
+ */
+pub fn update(world: &mut World, navigation: &Navigation, runes: &HashMap<String, Rune>, projectiles: &HashMap<String, Projectile>, delta: f32) {
+    for (id, mana) in world.manas.iter_mut() {
+        if let Some(sheet) = world.sheets.get(id) {
+            mana.regenerate(sheet.derived.mana_regen * delta);
+        }
+    }
+    for shield in world.shields.values_mut() {
+        shield.time -= delta;
+    }
+    world.shields.retain(|_, shield| shield.time > 0.0 && shield.absorb > 0);
+    for casting in world.castings.values_mut() {
+        casting.time -= delta;
+    }
+    let done = world.castings.iter()
+        .filter(|(_, casting)| casting.time <= 0.0)
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    for id in done {
+        if let Some(casting) = world.castings.remove(&id) {
+            release(world, navigation, runes, projectiles, id, &casting.spell, casting.aim);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::{data::mission::{Mission, Goal, Trigger}, events::GameEvent, logger::debug};
+
+use super::world::EntityId;
+
+//Something the player did that missions may count, most come from the game events on the bus
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    //the player came into the world or back to life
+    Spawned,
+    Died,
+    //the player killed an entity made from the data id
+    Killed(String),
+    //the player walked into the room of a location id
+    Entered(String),
+    //the player walked into a room chunk
+    Explored((u32, u32)),
+    //a mob following the player, by data id, is in the room of a location id
+    Escorted(String, String),
+    //the player handed an amount of an item id to a mob, by data id
+    Delivered(String, String, i32),
+    //the player stayed alive for a number of seconds
+    Survived(f32),
+    //a mission id was finished
+    Completed(String),
+    //how many of an item id the player carries right now
+    Carried(String, i32),
+}
+
+impl Event {
+    //what a game event means for the missions of the player, nothing when the player was not part of it
+    pub fn from_game(event: &GameEvent, player: EntityId) -> Vec<Self> {
+        match event {
+            GameEvent::EntitySpawned { id, .. } if *id == player => vec![Self::Spawned],
+            GameEvent::EntityDied { id, .. } if *id == player => vec![Self::Died],
+            //only kills of the player count
+            GameEvent::EntityDied { template: Some(template), killer: Some(killer), .. } if *killer == player => vec![Self::Killed(template.clone())],
+            GameEvent::ItemGiven { holder, to, item, amount } if *holder == player => vec![Self::Delivered(to.clone(), item.clone(), *amount)],
+            GameEvent::ChunkEntered { id, chunk, location } if *id == player => {
+                let mut events = vec![Self::Explored(*chunk)];
+                events.extend(location.iter().map(|location| Self::Entered(location.clone())));
+                events
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Status {
+    //waiting for its trigger
+    Inactive,
+    Active,
+    Complete,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self::Inactive
+    }
+}
+
+//A mission and how far along it is, the counters of its goal are the progress
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub mission: Mission,
+    pub status: Status,
+}
+
+impl Entry {
+    //whether the event starts the mission
+    fn is_triggered(&self, event: &Event) -> bool {
+        match (&self.mission.trigger, event) {
+            (Trigger::Spawn, Event::Spawned) => true,
+            (Trigger::Death, Event::Died) => true,
+            (Trigger::None, Event::Entered(location)) => *location == self.mission.location,
+            _ => false,
+        }
+    }
+
+    //counts the event towards the goal, returns whether the goal was reached
+    fn advance(&mut self, event: &Event) -> bool {
+        match (&mut self.mission.goal, event) {
+            (Goal::Hunt(hunt), Event::Killed(id)) if *id == hunt.target => {
+                hunt.count = (hunt.count + 1).min(hunt.number);
+            },
+            //what is carried counts, so items held before the mission started do and ones used up or handed over stop counting
+            (Goal::Collect(collect), Event::Carried(id, amount)) if *id == collect.item => {
+                collect.count = (*amount).clamp(0, collect.number);
+            },
+            (Goal::Explore(explore), Event::Entered(location)) if explore.location.as_ref() == Some(location) => {
+                explore.count = explore.number;
+            },
+            (Goal::Explore(explore), Event::Explored(room)) if explore.location.is_none() && !explore.visited.contains(room) => {
+                explore.visited.push(*room);
+                explore.count = (explore.count + 1).min(explore.number);
+            },
+            (Goal::Escort(escort), Event::Escorted(target, location)) if *target == escort.target && *location == escort.location => {
+                escort.arrived = true;
+            },
+            (Goal::Survive(survive), Event::Survived(delta)) => {
+                survive.elapsed += delta;
+            },
+            (Goal::Survive(survive), Event::Died) => {
+                survive.elapsed = 0.0;
+            },
+            (Goal::Deliver(deliver), Event::Delivered(to, id, amount)) if *to == deliver.to && *id == deliver.item => {
+                deliver.count = (deliver.count + amount).min(deliver.number);
+            },
+            (Goal::Chain(chain), Event::Completed(id)) if chain.missions.get(chain.current) == Some(id) => {
+                chain.current += 1;
+            },
+            _ => {},
+        }
+        self.is_done()
+    }
+
+    pub fn is_done(&self) -> bool {
+        match &self.mission.goal {
+            Goal::Hunt(hunt) => hunt.count >= hunt.number,
+            Goal::Collect(collect) => collect.count >= collect.number,
+            Goal::Explore(explore) => explore.count >= explore.number,
+            Goal::Escort(escort) => escort.arrived,
+            Goal::Survive(survive) => survive.elapsed >= survive.duration,
+            Goal::Deliver(deliver) => deliver.count >= deliver.number,
+            Goal::Chain(chain) => chain.current >= chain.missions.len(),
+        }
+    }
+
+    //what is left to do, names are looked up by data id
+    pub fn describe(&self, names: &HashMap<String, String>) -> String {
+        let name = |id: &String| names.get(id).cloned().unwrap_or_else(|| id.clone());
+        match &self.mission.goal {
+            Goal::Hunt(hunt) => format!("Hunt {}: {}/{}", name(&hunt.target), hunt.count, hunt.number),
+            Goal::Collect(collect) => format!("Collect {}: {}/{}", name(&collect.item), collect.count, collect.number),
+            Goal::Explore(explore) => match &explore.location {
+                Some(location) => format!("Find {}", name(location)),
+                None => format!("Explore rooms: {}/{}", explore.count, explore.number),
+            },
+            Goal::Escort(escort) => format!("Escort {} to {}", name(&escort.target), name(&escort.location)),
+            Goal::Survive(survive) => format!("Survive: {:.0}/{:.0}s", survive.elapsed.min(survive.duration), survive.duration),
+            Goal::Deliver(deliver) => format!("Bring {} to {}: {}/{}", name(&deliver.item), name(&deliver.to), deliver.count, deliver.number),
+            Goal::Chain(chain) => match chain.missions.get(chain.current) {
+                Some(step) => format!("Step {}/{}: {}", chain.current + 1, chain.missions.len(), name(step)),
+                None => format!("Step {}/{}", chain.missions.len(), chain.missions.len()),
+            },
+        }
+    }
+}
+
+//Every mission of the map and its progress
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Journal {
+    pub entries: HashMap<String, Entry>,
+    //display names of the mobs, items and locations goals refer to, keyed by data id
+    pub names: HashMap<String, String>,
+}
+
+impl Journal {
+    pub fn new(missions: &HashMap<String, Mission>, mut names: HashMap<String, String>) -> Self {
+        //chains show the names of their steps
+        names.extend(missions.values().map(|mission| (mission.id.clone(), mission.name.clone())));
+        Self {
+            entries: missions.iter()
+                .map(|(id, mission)| (id.clone(), Entry { mission: mission.clone(), status: Status::Inactive }))
+                .collect(),
+            names,
+        }
+    }
+
+    //takes the progress of a saved journal, missions that are no longer in the data are left out
+    pub fn restore(&mut self, saved: Journal) {
+        for (id, entry) in saved.entries {
+            if let Some(current) = self.entries.get_mut(&id) {
+                *current = entry;
+            }
+        }
+    }
+
+    //goals of the missions being worked on, the scene looks through them to know what the player can do with a mob
+    pub fn active(&self) -> impl Iterator<Item = &Goal> {
+        self.entries.values()
+            .filter(|entry| entry.status == Status::Active)
+            .map(|entry| &entry.mission.goal)
+    }
+
+    //starts the step each active chain is on
+    fn start_chains(&mut self) {
+        let steps = self.active()
+            .filter_map(|goal| match goal {
+                Goal::Chain(chain) => chain.missions.get(chain.current).cloned(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for step in steps {
+            if let Some(entry) = self.entries.get_mut(&step).filter(|entry| entry.status == Status::Inactive) {
+                debug!("Started {}", entry.mission.name);
+                entry.status = Status::Active;
+            }
+        }
+    }
+
+    /**
+     * Starts the missions an event triggers and counts it towards the active ones, finishing a mission counts towards chains
+     * event: &Event - What the player did
+     * returns: Vec<String> - Ids of the missions the event finished
+     */
+    pub fn handle(&mut self, event: &Event) -> Vec<String> {
+        let mut finished = Vec::new();
+        let mut events = VecDeque::from([event.clone()]);
+        while let Some(event) = events.pop_front() {
+            for (id, entry) in self.entries.iter_mut() {
+                if entry.status == Status::Inactive && entry.is_triggered(&event) {
+                    debug!("Started {}", entry.mission.name);
+                    entry.status = Status::Active;
+                }
+                if entry.status == Status::Active && entry.advance(&event) {
+                    entry.status = Status::Complete;
+                    finished.push(id.clone());
+                    events.push_back(Event::Completed(id.clone()));
+                }
+            }
+            self.start_chains();
+        }
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::mission::{Hunt, Explore, Chain};
+
+    fn mission(id: &str, goal: Goal, trigger: Trigger) -> Mission {
+        Mission {
+            id: id.to_string(),
+            name: id.to_string(),
+            location: "location.camp".to_string(),
+            goal,
+            trigger,
+            reward: Default::default(),
+        }
+    }
+
+    fn hunt(number: i32) -> Goal {
+        Goal::Hunt(Hunt { number, count: 0, target: "mob.zombie".to_string() })
+    }
+
+    fn find(location: &str) -> Goal {
+        Goal::Explore(Explore { number: 1, count: 0, location: Some(location.to_string()), visited: Vec::new() })
+    }
+
+    fn journal(missions: Vec<Mission>) -> Journal {
+        Journal::new(&missions.into_iter().map(|mission| (mission.id.clone(), mission)).collect(), HashMap::new())
+    }
+
+    fn status(journal: &Journal, id: &str) -> Status {
+        journal.entries[id].status.clone()
+    }
+
+    #[test]
+    fn missions_wait_for_their_trigger() {
+        let mut journal = journal(vec![mission("hunt", hunt(1), Trigger::Spawn)]);
+        assert!(journal.handle(&Event::Killed("mob.zombie".to_string())).is_empty());
+        assert_eq!(status(&journal, "hunt"), Status::Inactive);
+        journal.handle(&Event::Spawned);
+        assert_eq!(status(&journal, "hunt"), Status::Active);
+    }
+
+    #[test]
+    fn hunts_count_kills_of_their_target() {
+        let mut journal = journal(vec![mission("hunt", hunt(2), Trigger::Spawn)]);
+        journal.handle(&Event::Spawned);
+        assert!(journal.handle(&Event::Killed("mob.villager".to_string())).is_empty());
+        assert!(journal.handle(&Event::Killed("mob.zombie".to_string())).is_empty());
+        assert_eq!(journal.handle(&Event::Killed("mob.zombie".to_string())), vec!["hunt".to_string()]);
+        assert_eq!(status(&journal, "hunt"), Status::Complete);
+        //finished missions do not finish again
+        assert!(journal.handle(&Event::Killed("mob.zombie".to_string())).is_empty());
+    }
+
+    #[test]
+    fn entering_the_location_starts_untriggered_missions() {
+        let mut journal = journal(vec![mission("hunt", hunt(1), Trigger::None)]);
+        journal.handle(&Event::Entered("location.cave".to_string()));
+        assert_eq!(status(&journal, "hunt"), Status::Inactive);
+        journal.handle(&Event::Entered("location.camp".to_string()));
+        assert_eq!(status(&journal, "hunt"), Status::Active);
+    }
+
+    #[test]
+    fn chains_start_their_steps_in_order() {
+        let chain = Goal::Chain(Chain { missions: vec!["find".to_string(), "hunt".to_string()], current: 0 });
+        let mut journal = journal(vec![
+            mission("chain", chain, Trigger::Spawn),
+            mission("find", find("location.camp"), Trigger::Chain),
+            mission("hunt", hunt(1), Trigger::Chain),
+        ]);
+        journal.handle(&Event::Spawned);
+        assert_eq!(status(&journal, "find"), Status::Active);
+        assert_eq!(status(&journal, "hunt"), Status::Inactive);
+        //steps that are not started yet count nothing
+        assert!(journal.handle(&Event::Killed("mob.zombie".to_string())).is_empty());
+        assert_eq!(journal.handle(&Event::Entered("location.camp".to_string())), vec!["find".to_string()]);
+        assert_eq!(status(&journal, "hunt"), Status::Active);
+        let mut finished = journal.handle(&Event::Killed("mob.zombie".to_string()));
+        finished.sort();
+        assert_eq!(finished, vec!["chain".to_string(), "hunt".to_string()]);
+    }
+}
\ No newline at end of file
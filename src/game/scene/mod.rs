@@ -1,29 +1,68 @@
 pub mod location;
 pub mod entity;
+pub mod animation;
+pub mod paper_doll;
+pub mod camera;
+pub mod world;
+pub mod components;
+pub mod systems;
+pub mod stats;
+pub mod combat;
+pub mod status;
+pub mod navigation;
+pub mod ai;
+pub mod spawner;
+pub mod loot;
+pub mod progression;
+pub mod durability;
+pub mod magic;
+pub mod projectile;
+pub mod mission;
+pub mod save;
 
 use std::{collections::HashMap, fmt::{Debug, Formatter}};
 
-use crate::core::{logger::{error}, data::dungeon::{Dungeon, DungeonChunk}, toml_loader};
+use crate::core::{logger::{error, debug}, events::{EventBus, GameEvent}, data::{dungeon::{Dungeon, DungeonChunk}, affinity::AffinityTable, level::Levels, affix::Affix, rune::Rune}, toml_loader};
 
-use self::entity::{Character};
-use ggez::graphics;
-use image::GenericImage;
+use self::{camera::Camera, world::{World, EntityId}, combat::{Attack, Hit}, navigation::{Navigation, to_tile}};
 use rand::Rng;
 
-use crate::core::{toml_loader::{Size, TomlAsset, Configuration}, data, Direction};
+use crate::core::{toml_loader::{Size, TomlAsset, Configuration, TextureMap}, data, Direction, Motion};
 use serde::{Serialize, Deserialize};
 use location::Location;
 use serde_with::serde_as;
 
 const DATADIR: &str = "core/data";
+//Chunks away from the player that friendly mobs can be talked to from
+const TALK_REACH: f32 = 0.3;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Scene {
     pub map: Option<Map>,
-    pub camera: (f32, f32),
+    pub camera: Camera,
+    //every live entity, including the player
+    pub world: World,
+    pub player: Option<EntityId>,
+    //where the player comes back after dying, in chunks
+    pub spawn_point: (f32, f32),
+    pub affinities: AffinityTable,
+    pub levels: Levels,
+    //extra stats that can roll on items as they are handed out
+    pub affixes: Vec<Affix>,
+    //runes spells are made of, keyed by id
+    pub runes: HashMap<String, Rune>,
+    //what can be shot, keyed by id
+    pub projectiles: HashMap<String, entity::Projectile>,
+    //missions of the map and how far along they are
+    pub journal: mission::Journal,
+    //what happened this tick, systems publish to it and the ones after them react
     #[serde(skip)]
-    // 0 -> down, 1 -> left, 2 -> right, 3 -> up
-    pub direction: Direction,
+    pub bus: EventBus,
+    //room chunk the player was last in
+    #[serde(skip)]
+    pub room: Option<(u32, u32)>,
+    //spawn rules of the locations in the dungeon
+    pub spawners: Vec<spawner::Spawner>,
 }
 
 impl Scene {
@@ -38,13 +77,51 @@ impl Scene {
         let mut mob_map: HashMap<String, data::mob::Mob> = HashMap::new();
         let mut race_map: HashMap<String, data::race::Race> = HashMap::new();
         let mut file_string = {
-            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "classes.toml")) {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "affinities.toml")) {
                 ok
             } else {
                 String::new()
             }
         };
         let mut toml = toml::from_str::<TomlAsset>(&file_string);
+        if let Ok(ok) = toml {
+            match ok {
+                TomlAsset::Affinities(elements) => {
+                    scene.affinities = AffinityTable::new(elements);
+                },
+                _ => panic!("Could not load affinities file!"),
+            }
+        } else {
+            error!("{}", toml.err().unwrap());
+            panic!("Could not load affinities file!");
+        }
+        file_string = {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "levels.toml")) {
+                ok
+            } else {
+                String::new()
+            }
+        };
+        toml = toml::from_str::<TomlAsset>(&file_string);
+        if let Ok(ok) = toml {
+            match ok {
+                TomlAsset::Levels(levels) => {
+                    scene.levels = levels;
+                },
+                _ => panic!("Could not load levels file!"),
+            }
+        } else {
+            error!("{}", toml.err().unwrap());
+            panic!("Could not load levels file!");
+        }
+        file_string = {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "classes.toml")) {
+                ok
+            } else {
+                String::new()
+            }
+        };
+        toml = toml::from_str::<TomlAsset>(&file_string);
         if let Ok(ok) = toml {
             match ok {
                 TomlAsset::Classes(classes) => {
@@ -79,6 +156,68 @@ impl Scene {
             error!("{}", toml.err().unwrap());
             panic!("Could not load effects file!");
         }
+        file_string = {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "projectiles.toml")) {
+                ok
+            } else {
+                String::new()
+            }
+        };
+        toml = toml::from_str::<TomlAsset>(&file_string);
+        if let Ok(ok) = toml {
+            match ok {
+                TomlAsset::Projectiles(projectiles) => {
+                    for projectile in projectiles {
+                        let effect = effect_map.get(&projectile.effect).cloned();
+                        scene.projectiles.insert(projectile.id.clone(), entity::Projectile::new(projectile, effect));
+                    }
+                },
+                _ => panic!("Could not load projectiles file!"),
+            }
+        } else {
+            error!("{}", toml.err().unwrap());
+            panic!("Could not load projectiles file!");
+        }
+        file_string = {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "magic.toml")) {
+                ok
+            } else {
+                String::new()
+            }
+        };
+        toml = toml::from_str::<TomlAsset>(&file_string);
+        if let Ok(ok) = toml {
+            match ok {
+                TomlAsset::Runes(runes) => {
+                    for rune in runes {
+                        scene.runes.insert(rune.id.clone(), rune);
+                    }
+                },
+                _ => panic!("Could not load magic file!"),
+            }
+        } else {
+            error!("{}", toml.err().unwrap());
+            panic!("Could not load magic file!");
+        }
+        file_string = {
+            if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "affixes.toml")) {
+                ok
+            } else {
+                String::new()
+            }
+        };
+        toml = toml::from_str::<TomlAsset>(&file_string);
+        if let Ok(ok) = toml {
+            match ok {
+                TomlAsset::Affixes(affixes) => {
+                    scene.affixes = affixes;
+                },
+                _ => panic!("Could not load affixes file!"),
+            }
+        } else {
+            error!("{}", toml.err().unwrap());
+            panic!("Could not load affixes file!");
+        }
         file_string = {
             if let Ok(ok) = std::fs::read_to_string(format!("{}/{}", DATADIR, "items.toml")) {
                 ok
@@ -220,58 +359,508 @@ impl Scene {
             error!("{}", toml.err().unwrap());
             panic!("Could not load dungeon file!");
         };
-        scene.map = Some(Map::new(config, cha, _locs, class_map, effect_map, item_map, mission_map, mob_map, race_map, dungeon));
-        scene.camera = (0.0,0.0);
+        //affinities that are not defined still work, they just never change any damage
+        let affinities = race_map.values().map(|race| (&race.id, &race.affinity))
+            .chain(mob_map.values().map(|mob| (&mob.id, &mob.affinity)))
+            .chain(std::iter::once((&cha.name, &cha.affinity)));
+        for (id, affinity) in affinities {
+            if !scene.affinities.contains(affinity) {
+                error!("Affinity {} of {} is not defined in affinities.toml", affinity.0, id);
+            }
+        }
+        //goals refer to mobs, items and locations by id, the quest log shows their names
+        let names = mob_map.values().map(|mob| (mob.id.clone(), mob.name.clone()))
+            .chain(item_map.values().map(|item| (item.id.clone(), item.name.clone())))
+            .chain(_locs.iter().map(|location| (location.id.clone(), location.name.clone())))
+            .collect();
+        let map = Map::new(config, cha, _locs, &scene.affixes, class_map, effect_map, item_map, mission_map, mob_map, race_map, dungeon);
+        scene.spawners = spawner::Spawner::from_dungeon(&map.dungeon);
+        scene.journal = mission::Journal::new(&map.missions, names);
+        scene.map = Some(map);
+        scene.camera = Camera::default();
         scene
     }
 
-    pub fn set_camera(&mut self, pos: (f32, f32)) {
-        self.camera = pos;
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
     }
 
-    pub fn move_vert(&mut self, amount: f32) {
-        if amount > 0.0 {
-            self.direction = Direction::Down;
-        } else if amount < 0.0 {
-            self.direction = Direction::Up;
+    //spawns the player from the character template, pos is in chunks
+    pub fn place_character(&mut self, pos: (f32, f32)) {
+        self.sync_character();
+        let character = self.map.as_ref().unwrap().character.as_ref().unwrap();
+        let player = character.spawn(&mut self.world, pos);
+        self.player = Some(player);
+        self.spawn_point = pos;
+        self.bus.publish(GameEvent::EntitySpawned { id: player, template: None });
+    }
+
+    //the character template falls behind the player while playing, this brings it up to date before it is read again
+    pub fn sync_character(&mut self) {
+        if let (Some(player), Some(character)) = (self.player, self.map.as_mut().and_then(|map| map.character.as_mut())) {
+            character.sync(&self.world, player);
         }
-        self.camera.1 += amount;
-        self.map.as_mut().unwrap().character.as_mut().unwrap().move_vert(((amount % 1.0) * 10.0) as i32);
+    }
+
+    //writes the player to the store of the character, so the next game can be loaded from it
+    pub fn save(&mut self) {
+        self.sync_character();
+        let character = if let Some(character) = self.map.as_ref().and_then(|map| map.character.clone()) {
+            character
+        } else {
+            return;
+        };
+        let store = character.store.clone();
+        match (save::Save { character, journal: self.journal.clone() }).write() {
+            Ok(()) => {
+                debug!("Saved to {}", store);
+            },
+            Err(err) => {
+                error!("Could not save to {}: {}", store, err);
+            },
+        }
+    }
+
+    //swaps the character template and mission progress for the ones in its store before the player is placed, returns whether there was one
+    pub fn load(&mut self) -> bool {
+        let character = if let Some(character) = self.map.as_mut().and_then(|map| map.character.as_mut()) {
+            character
+        } else {
+            return false;
+        };
+        match save::Save::read(&character.store) {
+            Ok(save) => {
+                debug!("Loaded {}", character.store);
+                *character = save.character;
+                self.journal.restore(save.journal);
+                true
+            },
+            Err(err) => {
+                error!("Could not load {}: {}", character.store, err);
+                false
+            },
+        }
+    }
+
+    pub fn move_vert(&mut self, amount: f32) {
+        let direction = if amount > 0.0 { Direction::Down } else { Direction::Up };
+        self.move_player((0.0, amount), direction);
     }
 
     pub fn move_horiz(&mut self, amount: f32) {
-        if amount > 0.0 {
-            self.direction = Direction::Right;
-        } else if amount < 0.0 {
-            self.direction = Direction::Left;
-        }
-        self.camera.0 += amount;
-        self.map.as_mut().unwrap().character.as_mut().unwrap().move_hor(((amount % 1.0) * 10.0) as i32);
-    }
-
-    pub fn set_char_text(&mut self, ctx: &mut ggez::Context, textures: HashMap<String, image::ImageBuffer<image::Rgba<u8>, Vec<u8>>>) {
-        let character = self.map.as_mut().unwrap().character.as_mut().unwrap();
-        let race = character.race.id.clone();
-        let mut texture = textures.get(&race).unwrap().clone().sub_image(0, 0, 16, 32).to_image();
-        let mut writer = std::io::Cursor::new(Vec::new());
-        texture.write_to(&mut writer, image::ImageOutputFormat::Png).unwrap();
-        character.push_texture(graphics::Image::from_bytes(ctx, &writer.into_inner()).unwrap());
-        let right = textures.get(&race).unwrap().clone().sub_image(16, 0, 16, 32).to_image();
-        let left = image::imageops::flip_horizontal(&right);
-        writer = std::io::Cursor::new(Vec::new());
-        left.write_to(&mut writer, image::ImageOutputFormat::Png).unwrap();
-        character.push_texture(graphics::Image::from_bytes(ctx, &writer.into_inner()).unwrap());
-        writer = std::io::Cursor::new(Vec::new());
-        right.write_to(&mut writer, image::ImageOutputFormat::Png).unwrap();
-        character.push_texture(graphics::Image::from_bytes(ctx, &writer.into_inner()).unwrap());
-        texture = textures.get(&race).unwrap().clone().sub_image(32, 0, 16, 32).to_image();
-        writer = std::io::Cursor::new(Vec::new());
-        texture.write_to(&mut writer, image::ImageOutputFormat::Png).unwrap();
-        character.push_texture(graphics::Image::from_bytes(ctx, &writer.into_inner()).unwrap());
-    }
-
-    pub fn get_character_cln(&self) -> Character {
-        self.map.as_ref().unwrap().character.as_ref().unwrap().clone()
+        let direction = if amount > 0.0 { Direction::Right } else { Direction::Left };
+        self.move_player((amount, 0.0), direction);
+    }
+
+    //amount is in chunks
+    fn move_player(&mut self, amount: (f32, f32), direction: Direction) {
+        let player = if let Some(player) = self.player {
+            player
+        } else {
+            return;
+        };
+        let mut moved = if let Some(position) = self.world.positions.get(&player) {
+            position.clone()
+        } else {
+            return;
+        };
+        moved.x += amount.0;
+        moved.y += amount.1;
+        moved.direction = direction;
+        let map = self.map.as_ref().unwrap();
+        let walkable = Navigation::new(&map.dungeon, &map.solid).is_walkable(to_tile(moved.get()));
+        //solid tiles and entities block the player, but it still turns to face them
+        if !walkable || self.world.is_blocked(player, &moved) {
+            moved.x -= amount.0;
+            moved.y -= amount.1;
+        }
+        self.world.positions.insert(player, moved);
+    }
+
+    //plays the walk clip while moving, delta is in seconds
+    pub fn animate(&mut self, moving: bool, delta: f32) {
+        if let Some(sprite) = self.player.and_then(|player| self.world.sprites.get_mut(&player)) {
+            sprite.motion = if moving { Motion::Walk } else { Motion::Idle };
+        }
+        systems::animate(&mut self.world, delta);
+    }
+
+    //attacks with the player, aim is the direction to attack in
+    pub fn player_attack(&mut self, kind: Attack, aim: (f32, f32)) {
+        let player = if let Some(player) = self.player {
+            player
+        } else {
+            return;
+        };
+        //shots fly on their own and land later
+        if kind == Attack::Ranged {
+            if let Some(arrow) = self.projectiles.get(projectile::ARROW) {
+                projectile::fire(&mut self.world, arrow, player, aim);
+            }
+            return;
+        }
+        let hits = combat::attack(&mut self.world, &self.affinities, player, kind, aim);
+        self.publish_hits(hits);
+    }
+
+    fn publish_hits(&mut self, hits: Vec<Hit>) {
+        self.bus.publish_all(hits.into_iter().map(|hit| GameEvent::DamageDealt { attacker: hit.attacker, target: hit.target, damage: hit.damage, critical: hit.critical }));
+    }
+
+    //starts casting the spell of the player, aim is the direction to cast in, returns seconds until it goes off
+    pub fn cast(&mut self, aim: (f32, f32)) -> Result<f32, magic::Fizzle> {
+        if let Some(player) = self.player {
+            magic::cast(&mut self.world, &self.runes, player, aim)
+        } else {
+            Err(magic::Fizzle::NotReady)
+        }
+    }
+
+    //adds a rune to the end of the spell of the player, returns whether it was added
+    pub fn push_rune(&mut self, id: &str, value: Option<i32>) -> bool {
+        let (rune, spell) = if let (Some(rune), Some(spell)) = (self.runes.get(id), self.player.and_then(|player| self.world.spells.get_mut(&player))) {
+            (rune, spell)
+        } else {
+            return false;
+        };
+        spell.push(rune, value)
+    }
+
+    //takes the last rune off the spell of the player
+    pub fn pop_rune(&mut self) {
+        if let Some(spell) = self.player.and_then(|player| self.world.spells.get_mut(&player)) {
+            spell.pop();
+        }
+    }
+
+    pub fn clear_spell(&mut self) {
+        if let Some(spell) = self.player.and_then(|player| self.world.spells.get_mut(&player)) {
+            spell.clear();
+        }
+    }
+
+    //direction the player faces
+    pub fn get_player_facing(&self) -> (f32, f32) {
+        self.player
+            .and_then(|player| self.world.positions.get(&player))
+            .map(|position| position.facing())
+            .unwrap_or((0.0, 1.0))
+    }
+
+    //publishes the hits of projectiles landing and of mobs attacking or touching the player, delta is in seconds
+    pub fn update_combat(&mut self, delta: f32) {
+        let map = self.map.as_ref().unwrap();
+        let navigation = Navigation::new(&map.dungeon, &map.solid);
+        combat::update(&mut self.world, &navigation, delta);
+        magic::update(&mut self.world, &navigation, &self.runes, &self.projectiles, delta);
+        let mut hits = projectile::update(&mut self.world, &navigation, &self.affinities, delta);
+        hits.extend(ai::update(&mut self.world, &navigation, &self.affinities, delta));
+        hits.extend(combat::contact(&mut self.world, &self.affinities));
+        self.publish_hits(hits);
+    }
+
+    //spawns entities at the locations near the player, delta is in seconds
+    pub fn update_spawns(&mut self, delta: f32) {
+        let player = self.get_player_position();
+        let map = self.map.as_ref().unwrap();
+        spawner::update(&mut self.spawners, &mut self.world, &mut self.bus, &Navigation::new(&map.dungeon, &map.solid), player, delta);
+    }
+
+    //removes dead entities and brings the player back to the spawn point, whoever killed them gets their loot and experience
+    pub fn reap(&mut self) {
+        for id in combat::dead(&self.world) {
+            let killer = self.world.combatants.get(&id).and_then(|combatant| combatant.attacker);
+            self.bus.publish(GameEvent::EntityDied { id, template: self.world.templates.get(&id).cloned(), killer });
+            if Some(id) != self.player {
+                debug!("{} died", self.world.names.get(&id).map_or("Entity", |name| name.as_str()));
+                loot::scatter(&mut self.world, &self.affixes, id);
+                let level_ups = progression::reward(&mut self.world, &self.levels, id);
+                self.publish_level_ups(level_ups);
+                self.world.despawn(id);
+                continue;
+            }
+            debug!("Player died");
+            if let Some(position) = self.world.positions.get_mut(&id) {
+                position.x = self.spawn_point.0;
+                position.y = self.spawn_point.1;
+            }
+            if let Some(health) = self.world.healths.get_mut(&id) {
+                health.current = health.max;
+            }
+            //a spell still being cast fizzles, its mana is given back with the refill below
+            if self.world.castings.remove(&id).is_some() {
+                debug!("Casting fizzled on death");
+            }
+            if let Some(mana) = self.world.manas.get_mut(&id) {
+                mana.current = mana.max as f32;
+            }
+            if let Some(combatant) = self.world.combatants.get_mut(&id) {
+                *combatant = combat::Combatant::default();
+            }
+            self.bus.publish(GameEvent::EntitySpawned { id, template: None });
+        }
+    }
+
+    fn publish_level_ups(&mut self, level_ups: Vec<progression::LevelUp>) {
+        self.bus.publish_all(level_ups.into_iter().map(|level_up| GameEvent::LevelGained { id: level_up.id, level: level_up.level }));
+    }
+
+    //room chunk the player is in, None outside of the dungeon
+    fn player_room(&self) -> Option<(u32, u32)> {
+        let (x, y) = self.get_player_position();
+        let size = self.map.as_ref()?.dungeon.get_size();
+        if x < 0.0 || y < 0.0 || x as u32 >= size.0 || y as u32 >= size.1 {
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+
+    //gives the reward of a finished mission to the player
+    fn reward(&mut self, id: &str) {
+        let (player, reward) = if let (Some(player), Some(entry)) = (self.player, self.journal.entries.get(id)) {
+            (player, entry.mission.reward.clone())
+        } else {
+            return;
+        };
+        let level_ups = progression::award(&mut self.world, &self.levels, player, reward.experience);
+        self.publish_level_ups(level_ups);
+        let position = self.get_player_position();
+        for reward in reward.items {
+            let item = if let Some(item) = self.map.as_ref().and_then(|map| map.items.get(&reward.id)) {
+                item.instance(&self.affixes)
+            } else {
+                error!("Mission {} rewards unknown item {}", id, reward.id);
+                continue;
+            };
+            let left = self.world.inventories.get_mut(&player).map_or(reward.amount, |inventory| inventory.store(&item, reward.amount));
+            //whatever does not fit in the bag is dropped at the feet of the player
+            if left > 0 {
+                item.drop(&mut self.world, position, left);
+            }
+        }
+    }
+
+    //publishes the player walking into another room chunk
+    pub fn update_room(&mut self) {
+        let room = self.player_room();
+        if room == self.room {
+            return;
+        }
+        self.room = room;
+        if let (Some(player), Some(chunk)) = (self.player, room) {
+            let location = self.map.as_ref().and_then(|map| map.dungeon.get_location(chunk)).map(|location| location.id.clone());
+            self.bus.publish(GameEvent::ChunkEntered { id: player, chunk, location });
+        }
+    }
+
+    //counts what the player did this tick towards their missions and rewards the ones that are finished
+    pub fn update_missions(&mut self, delta: f32) {
+        let player = if let Some(player) = self.player {
+            player
+        } else {
+            return;
+        };
+        let mut events = self.bus.subscribe(|event| Some(mission::Event::from_game(event, player))).flatten().collect::<Vec<_>>();
+        events.push(mission::Event::Survived(delta));
+        events.extend(self.escorted(player));
+        //collecting goals look at the bag every tick, it changes through pickups, drinking, trading and handing items over
+        let carried = self.journal.active()
+            .filter_map(|goal| match goal {
+                data::mission::Goal::Collect(collect) => Some(collect.item.clone()),
+                _ => None,
+            })
+            .map(|item| {
+                let amount = self.world.inventories.get(&player).map_or(0, |inventory| inventory.count(&item));
+                mission::Event::Carried(item, amount)
+            })
+            .collect::<Vec<_>>();
+        events.extend(carried);
+        //a place to find counts when the player is already standing in it as the goal becomes active, not only on walking in
+        let here = self.room.and_then(|room| self.map.as_ref()?.dungeon.get_location(room)).map(|location| location.id.clone());
+        let found = self.journal.active().any(|goal| match goal {
+            data::mission::Goal::Explore(explore) => explore.location.is_some() && explore.location == here,
+            _ => false,
+        });
+        if let (true, Some(here)) = (found, here) {
+            events.push(mission::Event::Entered(here));
+        }
+        for event in events {
+            for id in self.journal.handle(&event) {
+                let name = self.journal.entries.get(&id).map_or(id.clone(), |entry| entry.mission.name.clone());
+                debug!("Completed {}", name);
+                self.bus.publish(GameEvent::MissionCompleted { id: id.clone(), name });
+                self.release(&id);
+                self.reward(&id);
+            }
+        }
+    }
+
+    //mobs following the player that are in the room of a location
+    fn escorted(&self, player: EntityId) -> Vec<mission::Event> {
+        let dungeon = if let Some(map) = self.map.as_ref() {
+            &map.dungeon
+        } else {
+            return Vec::new();
+        };
+        self.world.ais.iter()
+            .filter(|(_, ai)| ai.leader == Some(player))
+            .filter_map(|(id, _)| {
+                let (x, y) = self.world.positions.get(id)?.get();
+                if x < 0.0 || y < 0.0 {
+                    return None;
+                }
+                let location = dungeon.get_location((x as u32, y as u32))?;
+                Some(mission::Event::Escorted(self.world.templates.get(id)?.clone(), location.id.clone()))
+            })
+            .collect()
+    }
+
+    //stops the mobs of a finished escort from following the player, they stay where they were brought
+    fn release(&mut self, id: &str) {
+        let target = if let Some(data::mission::Goal::Escort(escort)) = self.journal.entries.get(id).map(|entry| &entry.mission.goal) {
+            escort.target.clone()
+        } else {
+            return;
+        };
+        for (entity, ai) in self.world.ais.iter_mut() {
+            if ai.leader.is_some() && self.world.templates.get(entity) == Some(&target) {
+                ai.leader = None;
+                if let Some(position) = self.world.positions.get(entity) {
+                    ai.home = position.get();
+                }
+            }
+        }
+    }
+
+    //spends a free stat point of the player, stat is the index in the order of Stats
+    pub fn allocate(&mut self, stat: usize) {
+        if let Some(player) = self.player {
+            progression::allocate(&mut self.world, player, stat);
+        }
+    }
+
+    //picks up the items on the ground around the player
+    pub fn interact(&mut self) {
+        if let Some(player) = self.player {
+            for (item, amount) in loot::pick_up(&mut self.world, player) {
+                debug!("Picked up {} {}", amount, item.display_name());
+                self.bus.publish(GameEvent::ItemPickedUp { holder: player, item: item.id, amount });
+            }
+            self.talk(player);
+        }
+    }
+
+    //hands items to the friendly mobs around the player that missions want them brought to, and has the ones to escort follow
+    fn talk(&mut self, player: EntityId) {
+        let origin = self.get_player_position();
+        let nearby = self.world.ais.iter()
+            .filter(|(_, ai)| ai.friendly)
+            .filter(|(id, _)| self.world.positions.get(id).map_or(false, |position| {
+                let (x, y) = (position.x - origin.0, position.y - origin.1);
+                (x * x + y * y).sqrt() <= TALK_REACH
+            }))
+            //one of each kind is enough, handing to a second would give more than the mission asks for
+            .filter_map(|(id, _)| Some((self.world.templates.get(id)?.clone(), *id)))
+            .collect::<HashMap<_, _>>();
+        for (template, id) in nearby {
+            for goal in self.journal.active() {
+                match goal {
+                    data::mission::Goal::Deliver(deliver) if deliver.to == template => {
+                        let given = self.world.inventories.get_mut(&player).map_or(0, |inventory| inventory.take(&deliver.item, deliver.number - deliver.count));
+                        if given > 0 {
+                            debug!("Gave {} {} to {}", given, deliver.item, template);
+                            self.bus.publish(GameEvent::ItemGiven { holder: player, to: template.clone(), item: deliver.item.clone(), amount: given });
+                        }
+                    },
+                    data::mission::Goal::Escort(escort) if escort.target == template => {
+                        if let Some(ai) = self.world.ais.get_mut(&id) {
+                            ai.leader = Some(player);
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+    }
+
+    //moves items around the inventory of the player, returns whether anything moved
+    pub fn move_item(&mut self, from: &entity::SlotRef, to: &entity::SlotRef, amount: Option<i32>) -> bool {
+        self.player
+            .and_then(|player| self.world.inventories.get_mut(&player))
+            .map_or(false, |inventory| inventory.move_item(from, to, amount))
+    }
+
+    pub fn holds_consumable(&self) -> bool {
+        self.player
+            .and_then(|player| self.world.inventories.get(&player))
+            .map_or(false, |inventory| inventory.holding.values().any(|slot| slot.item.as_ref().map_or(false, |item| item.kind == data::item::Kind::Consumable)))
+    }
+
+    //uses the first consumable in the hands of the player, returns whether one was used
+    pub fn use_held(&mut self) -> bool {
+        let player = if let Some(player) = self.player {
+            player
+        } else {
+            return false;
+        };
+        let mut hands = self.world.inventories.get(&player)
+            .map(|inventory| inventory.holding.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        hands.sort();
+        hands.into_iter().any(|hand| status::use_item(&mut self.world, player, &entity::SlotRef::Holding(hand)))
+    }
+
+    //uses the consumable on a slot of the quickbar, wherever the player carries it
+    pub fn use_quick(&mut self, index: usize) -> bool {
+        let player = if let Some(player) = self.player {
+            player
+        } else {
+            return false;
+        };
+        let slot = self.world.inventories.get(&player)
+            .and_then(|inventory| inventory.quickbar.get(index).cloned().flatten().and_then(|id| inventory.find(&id)));
+        slot.map_or(false, |slot| status::use_item(&mut self.world, player, &slot))
+    }
+
+    //repairs an item of the player with its coins
+    pub fn repair(&mut self, slot: &entity::SlotRef) -> bool {
+        self.player.map_or(false, |player| durability::repair(&mut self.world, player, slot))
+    }
+
+    //puts a consumable on the quickbar, None clears the slot
+    pub fn assign_quick(&mut self, index: usize, item: Option<String>) {
+        let quickbar = self.player
+            .and_then(|player| self.world.inventories.get_mut(&player))
+            .and_then(|inventory| inventory.quickbar.get_mut(index));
+        if let Some(quick) = quickbar {
+            *quick = item;
+        }
+    }
+
+    //ticks status effects and publishes the damage they deal, delta is in seconds
+    pub fn update_effects(&mut self, delta: f32) {
+        let hits = status::update(&mut self.world, &self.affinities, delta);
+        self.publish_hits(hits);
+    }
+
+    pub fn update_stats(&mut self) {
+        systems::stats(&mut self.world);
+    }
+
+    pub fn dress(&mut self, texture_map: &TextureMap) {
+        systems::dress(&mut self.world, texture_map);
+    }
+
+    //position of the player in chunks
+    pub fn get_player_position(&self) -> (f32, f32) {
+        self.player
+            .and_then(|player| self.world.positions.get(&player))
+            .map(|position| position.get())
+            .unwrap_or_default()
+    }
+
+    pub fn get_player_sheet(&self) -> Option<&stats::Sheet> {
+        self.player.and_then(|player| self.world.sheets.get(&player))
     }
 }
 
@@ -283,11 +872,15 @@ pub struct Map {
     pub locations: HashMap<String, Location>,
     //includes missions
     pub missions: HashMap<String, data::mission::Mission>,
-    //includes character, race, class
-    pub character: Option<Character>,
+    //includes character, race, class, the template the player is spawned from
+    pub character: Option<entity::Character>,
+    //every item, keyed by id, that new instances are made from
+    pub items: HashMap<String, entity::Item>,
     pub size: Size,
     pub dungeon: Dungeon,
     pub dungeon_list: Vec<DungeonChunk>,
+    //tiles nothing can walk on
+    pub solid: Vec<u16>,
 }
 
 impl Debug for Map {
@@ -299,16 +892,20 @@ impl Debug for Map {
 }
 
 impl Map {
-    pub fn new(configs: Configuration, character: data::character::Character, locs: Vec<data::location::Location>, class_map: HashMap<String, data::class::Class>, effect_map: HashMap<String, data::effect::Effect>, item_map: HashMap<String, data::item::Item>, mission_map: HashMap<String, data::mission::Mission>, mob_map: HashMap<String, data::mob::Mob>, race_map: HashMap<String, data::race::Race>, dungeon: toml_loader::Dungeon) -> Self {
+    pub fn new(configs: Configuration, character: data::character::Character, locs: Vec<data::location::Location>, affixes: &[Affix], class_map: HashMap<String, data::class::Class>, effect_map: HashMap<String, data::effect::Effect>, item_map: HashMap<String, data::item::Item>, mission_map: HashMap<String, data::mission::Mission>, mob_map: HashMap<String, data::mob::Mob>, race_map: HashMap<String, data::race::Race>, dungeon: toml_loader::Dungeon) -> Self {
         let mut map = Self::default();
         let mut items: HashMap<String, entity::Item> = HashMap::new();
         let mut mobs: HashMap<String, entity::Mob> = HashMap::new();
         map.dungeon_list = dungeon.chunks.clone();
+        map.solid = dungeon.solid.clone();
         map.size = configs.settings.size.clone();
         map.missions = mission_map.clone();
 
         //create a map for items in persistent memory
         for item in item_map.values() {
+            for problem in item.validate() {
+                error!("Item {}: {}", item.id, problem);
+            }
             let rect = configs.tex_map.get(&item.texture).unwrap().clone();
             let effect = if let Some(effect) = effect_map.get(&item.effect) {
                 Some(effect.clone())
@@ -339,7 +936,8 @@ impl Map {
         }
 
         //create character
-        map.character = Some(entity::Character::new(character, items, class_map, race_map, (configs.settings.size.w as i32, configs.settings.size.h as i32)));
+        map.items = items.clone();
+        map.character = Some(entity::Character::new(character, items, affixes, class_map, race_map));
 
         map
     }
@@ -0,0 +1,201 @@
+use std::{collections::{BinaryHeap, HashMap}, cmp::Ordering};
+
+use crate::core::{data::dungeon::Dungeon, toml_loader::CHUNK_SIZE};
+
+//Most tiles a single search looks at, keeps unreachable goals from stalling a frame
+const SEARCH_LIMIT: usize = 2048;
+//Cost of a diagonal step
+const DIAGONAL: f32 = std::f32::consts::SQRT_2;
+
+//Walkable tiles of the dungeon, as the chunks are drawn after their rotations
+pub struct Navigation<'a> {
+    dungeon: &'a Dungeon,
+    solid: &'a [u16],
+}
+
+//A tile waiting to be searched, ordered so the heap pops the cheapest first
+#[derive(PartialEq)]
+struct Node {
+    estimate: f32,
+    tile: (i32, i32),
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.partial_cmp(&self.estimate).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//tile a position in chunks is on
+pub fn to_tile(position: (f32, f32)) -> (i32, i32) {
+    ((position.0 * CHUNK_SIZE).floor() as i32, (position.1 * CHUNK_SIZE).floor() as i32)
+}
+
+//center of a tile in chunks
+pub fn to_position(tile: (i32, i32)) -> (f32, f32) {
+    ((tile.0 as f32 + 0.5) / CHUNK_SIZE, (tile.1 as f32 + 0.5) / CHUNK_SIZE)
+}
+
+impl<'a> Navigation<'a> {
+    pub fn new(dungeon: &'a Dungeon, solid: &'a [u16]) -> Self {
+        Self { dungeon, solid }
+    }
+
+    //tiles outside of the dungeon are never walkable
+    pub fn is_walkable(&self, tile: (i32, i32)) -> bool {
+        self.dungeon.get_tile(tile).map_or(false, |tile| !self.solid.contains(&tile))
+    }
+
+    //octile distance, exact for a grid with diagonal steps
+    fn heuristic(a: (i32, i32), b: (i32, i32)) -> f32 {
+        let (x, y) = ((a.0 - b.0).abs() as f32, (a.1 - b.1).abs() as f32);
+        x.max(y) + (DIAGONAL - 1.0) * x.min(y)
+    }
+
+    /**
+     * Finds the shortest walkable path between two tiles with A*
+     * start: (i32, i32) - The tile to start from
+     * goal: (i32, i32) - The tile to reach
+     * returns: Option<Vec<(i32, i32)>> - The tiles to walk through in reverse, so the next one can be popped, None when the goal cannot be reached
+     */
+    pub fn find_path(&self, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        if !self.is_walkable(goal) {
+            return None;
+        }
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        let mut cost: HashMap<(i32, i32), f32> = HashMap::new();
+        open.push(Node { estimate: Self::heuristic(start, goal), tile: start });
+        cost.insert(start, 0.0);
+        let mut searched = 0;
+        while let Some(Node { tile, .. }) = open.pop() {
+            if tile == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while let Some(previous) = came_from.get(&current) {
+                    if *previous == start {
+                        break;
+                    }
+                    path.push(*previous);
+                    current = *previous;
+                }
+                return Some(path);
+            }
+            searched += 1;
+            if searched > SEARCH_LIMIT {
+                return None;
+            }
+            let current_cost = cost[&tile];
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                let next = (tile.0 + dx, tile.1 + dy);
+                if !self.is_walkable(next) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                //diagonal steps may not cut the corner of a solid tile
+                if diagonal && (!self.is_walkable((tile.0 + dx, tile.1)) || !self.is_walkable((tile.0, tile.1 + dy))) {
+                    continue;
+                }
+                let next_cost = current_cost + if diagonal { DIAGONAL } else { 1.0 };
+                if cost.get(&next).map_or(true, |known| next_cost < *known) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, tile);
+                    open.push(Node { estimate: next_cost + Self::heuristic(next, goal), tile: next });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //solid tile of the test dungeons
+    const WALL: u16 = 1;
+
+    //dungeon of a single chunk, # is a wall and anything else is floor
+    fn dungeon(rows: [&str; 10]) -> Dungeon {
+        let matrix = rows.iter()
+            .map(|row| row.chars().map(|tile| if tile == '#' { WALL } else { 0 }).collect::<Vec<u16>>())
+            .collect::<Vec<_>>();
+        let dungeon = format!("size = [1, 1]\nnet_weight = 0\nchunks = [[{{ matrix = {:?}, entries = 0, room = false, weight = 0, id = 0, rotations = 0 }}]]", matrix);
+        toml::from_str(&dungeon).unwrap()
+    }
+
+    //every step of a path goes to a walkable neighbour of the one before it
+    fn assert_walkable(navigation: &Navigation, start: (i32, i32), path: &[(i32, i32)]) {
+        let mut previous = start;
+        for tile in path.iter().rev() {
+            assert!(navigation.is_walkable(*tile), "{:?} is not walkable", tile);
+            assert!((tile.0 - previous.0).abs() <= 1 && (tile.1 - previous.1).abs() <= 1, "{:?} does not follow {:?}", tile, previous);
+            previous = *tile;
+        }
+    }
+
+    #[test]
+    fn blocked_goal_has_no_path() {
+        let dungeon = dungeon([
+            "..........",
+            "....#.....",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+            "..........",
+        ]);
+        let navigation = Navigation::new(&dungeon, &[WALL]);
+        assert_eq!(navigation.find_path((1, 1), (4, 1)), None);
+        assert_eq!(navigation.find_path((1, 1), (-1, 1)), None);
+        assert_eq!(navigation.find_path((1, 1), (10, 1)), None);
+    }
+
+    #[test]
+    fn open_floor_is_crossed_in_a_straight_line() {
+        let dungeon = dungeon([".........."; 10]);
+        let navigation = Navigation::new(&dungeon, &[WALL]);
+        let path = navigation.find_path((1, 1), (5, 1)).unwrap();
+        assert_eq!(path, vec![(5, 1), (4, 1), (3, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn walls_are_walked_around() {
+        let dungeon = dungeon([
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "....#.....",
+            "..........",
+        ]);
+        let navigation = Navigation::new(&dungeon, &[WALL]);
+        let path = navigation.find_path((2, 1), (6, 1)).unwrap();
+        assert_eq!(path.first(), Some(&(6, 1)));
+        assert_walkable(&navigation, (2, 1), &path);
+        //the only way through is the gap in the bottom row
+        assert!(path.contains(&(4, 9)));
+    }
+
+    #[test]
+    fn walled_off_goal_has_no_path() {
+        let dungeon = dungeon(["....#....."; 10]);
+        let navigation = Navigation::new(&dungeon, &[WALL]);
+        assert_eq!(navigation.find_path((2, 1), (6, 1)), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use ggez::glam;
+
+use crate::{core::{toml_loader::TextureMap, functions::Vector2D, logger::error, Direction, Motion}, game::batch::SpriteBatch};
+
+use super::{animation::{Animator, Frame}, entity::Inventory};
+
+//The slot name layering uses for the wearer itself
+pub const BASE: &str = "base";
+
+//Equipment drawn on top of the wearer
+#[derive(Clone, Debug)]
+struct Layer {
+    texture: String,
+    animator: Animator,
+    offset: Vector2D,
+}
+
+//Composites the sprites of equipped apparel and held items onto a character
+#[derive(Clone, Debug, Default)]
+pub struct PaperDoll {
+    layers: HashMap<String, Layer>,
+}
+
+impl PaperDoll {
+    /**
+     * Rebuilds the layers of the slots whose item changed since the last call
+     * texture_map: &TextureMap - Source of the item textures and animations
+     * inventory: &Inventory - The apparel and holding slots to dress from
+     */
+    pub fn dress(&mut self, texture_map: &TextureMap, inventory: &Inventory) {
+        for slot in inventory.apparel.values().chain(inventory.holding.values()) {
+            let texture = slot.item.as_ref().map(|item| &item.texture_id);
+            let current = self.layers.get(&slot.name).map(|layer| &layer.texture);
+            if texture == current {
+                continue;
+            }
+            let texture = if let Some(texture) = texture {
+                texture.clone()
+            } else {
+                self.layers.remove(&slot.name);
+                continue;
+            };
+            let rect = if let Some(rect) = texture_map.get_rect(&texture) {
+                rect
+            } else {
+                error!("Texture {} not found", texture);
+                self.layers.remove(&slot.name);
+                continue;
+            };
+            let atlas = texture_map.get_atlas_size();
+            let (animator, offset) = if let Some(animation) = texture_map.get_animation(&texture) {
+                (Animator::new(rect, atlas, animation), animation.offset.clone())
+            } else {
+                (Animator::still(rect, atlas), Vector2D::default())
+            };
+            self.layers.insert(slot.name.clone(), Layer { texture, animator, offset });
+        }
+    }
+
+    //delta is in seconds
+    pub fn animate(&mut self, motion: Motion, direction: Direction, delta: f32) {
+        for layer in self.layers.values_mut() {
+            layer.animator.set(motion.clone(), direction.clone());
+            layer.animator.update(delta);
+        }
+    }
+
+    /**
+     * Queues the wearer and its layers in the order given by the layering for the direction
+     * batch: &mut SpriteBatch - The batch to queue the frames in
+     * base: Option<&Frame> - The current frame of the wearer
+     * direction: &Direction - The direction the wearer is facing
+     * texture_map: &TextureMap - Source of the layering
+     * dest: glam::Vec2 - Where the wearer is drawn on screen
+     * scale: f32 - How much the wearer is scaled by
+     */
+    pub fn draw(&self, batch: &mut SpriteBatch, base: Option<&Frame>, direction: &Direction, texture_map: &TextureMap, dest: glam::Vec2, scale: f32) {
+        let layering = if let Some(layering) = texture_map.get_layering(direction) {
+            layering
+        } else {
+            //without a layering there is no telling where the equipment goes
+            if let Some(base) = base {
+                batch.push(base, dest, scale);
+            }
+            return;
+        };
+        for slot in &layering.slots {
+            let anchor = layering.anchors.get(slot).cloned().unwrap_or_default();
+            let (frame, offset) = if slot == BASE {
+                (base, Vector2D::default())
+            } else if let Some(layer) = self.layers.get(slot) {
+                (layer.animator.get_frame(), layer.offset.clone())
+            } else {
+                continue;
+            };
+            if let Some(frame) = frame {
+                batch.push(
+                    frame,
+                    glam::Vec2::new(
+                        dest.x + (offset.x + anchor.x) as f32 * scale,
+                        dest.y + (offset.y + anchor.y) as f32 * scale
+                    ),
+                    scale
+                );
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use rand::Rng;
+
+use crate::core::data::level::Levels;
+
+use super::{world::{World, EntityId}, entity::Drops};
+
+//An entity reaching a new level
+#[derive(Debug, Clone)]
+pub struct LevelUp {
+    pub id: EntityId,
+    pub level: i32,
+}
+
+//rolls the experience ranges of a drop table
+fn roll(drops: &[Drops]) -> i32 {
+    let mut rng = rand::thread_rng();
+    drops.iter()
+        .map(|drop| match drop {
+            Drops::Exp(range) => rng.gen_range(range.min.min(range.max)..=range.max.max(range.min)),
+            Drops::Item(_) => 0,
+        })
+        .sum()
+}
+
+/**
+ * Gives the experience of a dead entity to whoever killed it
+ * world: &mut World - The world the entities are in
+ * levels: &Levels - The experience curve
+ * id: EntityId - The entity that died
+ * returns: Vec<LevelUp> - Every level the killer reached
+ */
+pub fn reward(world: &mut World, levels: &Levels, id: EntityId) -> Vec<LevelUp> {
+    let killer = if let Some(killer) = world.combatants.get(&id).and_then(|combatant| combatant.attacker) {
+        killer
+    } else {
+        return Vec::new();
+    };
+    let level = world.levels.get(&id).map_or(1, |level| level.level);
+    let experience = world.drops.get(&id).map_or(0, |drops| levels.reward(roll(drops), level));
+    award(world, levels, killer, experience)
+}
+
+/**
+ * Adds experience to a character, levelling it up as many times as the experience allows
+ * world: &mut World - The world the character is in
+ * levels: &Levels - The experience curve
+ * id: EntityId - The character gaining experience
+ * experience: i32 - The experience gained
+ * returns: Vec<LevelUp> - Every level the character reached
+ */
+pub fn award(world: &mut World, levels: &Levels, id: EntityId, experience: i32) -> Vec<LevelUp> {
+    let mut level_ups = Vec::new();
+    //only characters have a class to grow from
+    let growth = if let Some(class) = world.classes.get(&id) {
+        class.stats.scaled(levels.class_growth)
+    } else {
+        return level_ups;
+    };
+    let level = if let Some(level) = world.levels.get_mut(&id) {
+        level
+    } else {
+        return level_ups;
+    };
+    if level.level >= levels.max {
+        return level_ups;
+    }
+    level.experience += experience.max(0);
+    while level.level < levels.max && level.experience >= levels.required(level.level) {
+        level.experience -= levels.required(level.level);
+        level.level += 1;
+        level.points += levels.points;
+        level_ups.push(LevelUp { id, level: level.level });
+    }
+    if level.level >= levels.max {
+        level.experience = 0;
+    }
+    if let Some(stats) = world.stats.get_mut(&id) {
+        for _ in &level_ups {
+            *stats += &growth;
+        }
+    }
+    level_ups
+}
+
+/**
+ * Spends a free stat point on a base stat
+ * world: &mut World - The world the character is in
+ * id: EntityId - The character spending the point
+ * stat: usize - Index of the stat in the order of Stats
+ * returns: bool - Whether there was a point to spend
+ */
+pub fn allocate(world: &mut World, id: EntityId, stat: usize) -> bool {
+    let (level, stats) = if let (Some(level), Some(stats)) = (world.levels.get_mut(&id), world.stats.get_mut(&id)) {
+        (level, stats)
+    } else {
+        return false;
+    };
+    if level.points <= 0 {
+        return false;
+    }
+    let value = match stat {
+        0 => &mut stats.stg,
+        1 => &mut stats.agi,
+        2 => &mut stats.dex,
+        3 => &mut stats.int,
+        4 => &mut stats.luk,
+        5 => &mut stats.vit,
+        6 => &mut stats.def,
+        7 => &mut stats.rng,
+        _ => return false,
+    };
+    *value += 1;
+    level.points -= 1;
+    true
+}
\ No newline at end of file
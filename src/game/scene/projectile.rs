@@ -0,0 +1,94 @@
+use crate::core::{data::{Stats, affinity::AffinityTable}, toml_loader::CHUNK_SIZE};
+
+use super::{world::{World, EntityId}, entity::Projectile, combat::{self, Attack, Hit}, navigation::{Navigation, to_tile}, status};
+
+//Id of the projectile shot with the use key
+pub const ARROW: &str = "projectile.arrow";
+
+/**
+ * Shoots a projectile if the shooter is not cooling down, it flies as far as the range of the shooter
+ * world: &mut World - The world the shooter is in
+ * projectile: &Projectile - What to shoot
+ * shooter: EntityId - The entity shooting
+ * aim: (f32, f32) - The direction to shoot in, does not need to be normalized
+ * returns: Option<EntityId> - The projectile, None when the shooter was not ready
+ */
+pub fn fire(world: &mut World, projectile: &Projectile, shooter: EntityId, aim: (f32, f32)) -> Option<EntityId> {
+    let ready = world.combatants.get(&shooter).map_or(false, |combatant| combatant.cooldown <= 0.0);
+    let (origin, sheet) = if let (true, Some(position), Some(sheet)) = (ready, world.positions.get(&shooter), world.sheets.get(&shooter)) {
+        (position.get(), sheet)
+    } else {
+        return None;
+    };
+    let length = (aim.0 * aim.0 + aim.1 * aim.1).sqrt();
+    if length == 0.0 {
+        return None;
+    }
+    let cooldown = 1.0 / sheet.derived.attack_speed.max(0.1);
+    let reach = combat::reach(world, shooter, Attack::Ranged);
+    let id = projectile.spawn(world, shooter, Attack::Ranged, origin, (aim.0 / length, aim.1 / length), reach);
+    if let Some(combatant) = world.combatants.get_mut(&shooter) {
+        combatant.cooldown = cooldown;
+    }
+    Some(id)
+}
+
+//first entity the projectile overlaps that its owner may hurt
+fn touching(world: &World, id: EntityId, owner: EntityId) -> Option<EntityId> {
+    let (position, collider) = (world.positions.get(&id)?, world.colliders.get(&id)?);
+    world.colliders.iter()
+        .filter(|(target, _)| **target != id && combat::is_hostile(world, owner, **target))
+        .find(|(target, target_collider)| world.positions.get(target).map_or(false, |target_position| collider.overlaps(position, target_collider, target_position)))
+        .map(|(target, _)| *target)
+}
+
+/**
+ * Moves projectiles a tile at a time, they fall apart on solid tiles, on the first entity they hit or when their lifetime runs out
+ * world: &mut World - The world the projectiles are in
+ * navigation: &Navigation - The tiles that stop projectiles
+ * affinities: &AffinityTable - How the affinities of the owners and targets change the damage
+ * delta: f32 - Seconds since the last update
+ * returns: Vec<Hit> - The damage dealt
+ */
+pub fn update(world: &mut World, navigation: &Navigation, affinities: &AffinityTable, delta: f32) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    let mut ids = world.projectiles.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        let flight = if let Some(flight) = world.projectiles.get_mut(&id) {
+            flight.lifetime -= delta;
+            flight.clone()
+        } else {
+            continue;
+        };
+        let speed = (flight.velocity.0 * flight.velocity.0 + flight.velocity.1 * flight.velocity.1).sqrt();
+        let direction = (flight.velocity.0 / speed.max(f32::EPSILON), flight.velocity.1 / speed.max(f32::EPSILON));
+        //moving a tile at a time keeps fast projectiles from skipping over walls and entities
+        let steps = (speed * delta * CHUNK_SIZE).ceil().max(1.0) as i32;
+        let mut landed = false;
+        for _ in 0..steps {
+            if let Some(position) = world.positions.get_mut(&id) {
+                position.x += flight.velocity.0 * delta / steps as f32;
+                position.y += flight.velocity.1 * delta / steps as f32;
+                if !navigation.is_walkable(to_tile(position.get())) {
+                    landed = true;
+                    break;
+                }
+            }
+            if let Some(target) = touching(world, id, flight.owner) {
+                if let Some(hit) = combat::strike(world, affinities, flight.owner, target, flight.kind, direction) {
+                    if let Some(effect) = flight.effect.as_ref() {
+                        status::apply(world, target, effect, &Stats::default(), Some(flight.owner), None);
+                    }
+                    hits.push(hit);
+                }
+                landed = true;
+                break;
+            }
+        }
+        if landed || flight.lifetime <= 0.0 {
+            world.despawn(id);
+        }
+    }
+    hits
+}
\ No newline at end of file
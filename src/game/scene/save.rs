@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use super::{entity::Character, mission::Journal};
+
+//Where $CONFIG$ in the store of a character points, outside of core so saving never breaks its checksum
+const CONFIGDIR: &str = "config";
+
+//What is kept of the player between games, written as toml to the store of the character
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Save {
+    //level, stats and every item instance the character carries, with its uuid, uses and affixes
+    pub character: Character,
+    //how far along every mission is
+    #[serde(default)]
+    pub journal: Journal,
+}
+
+impl Save {
+    //file a store path refers to
+    pub fn path(store: &str) -> PathBuf {
+        PathBuf::from(store.replace("$CONFIG$", CONFIGDIR))
+    }
+
+    pub fn write(&self) -> Result<(), String> {
+        let path = Self::path(&self.character.store);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let toml = toml::to_string_pretty(self).map_err(|err| err.to_string())?;
+        std::fs::write(path, toml).map_err(|err| err.to_string())
+    }
+
+    pub fn read(store: &str) -> Result<Self, String> {
+        let file_string = std::fs::read_to_string(Self::path(store)).map_err(|err| err.to_string())?;
+        toml::from_str::<Self>(&file_string).map_err(|err| err.to_string())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+
+use crate::core::{data::{dungeon::Dungeon, location::Trigger}, events::{EventBus, GameEvent}, toml_loader::CHUNK_SIZE, logger::debug};
+
+use super::{world::{World, EntityId}, location::Spawn, navigation::{Navigation, to_tile, to_position}};
+
+//Chunks away from the player that spawned entities are removed at
+const DESPAWN_DISTANCE: f32 = 3.0;
+//Most entities a single location keeps alive at once
+const MAX_ALIVE: usize = 4;
+//Tiles tried before a spawn is given up on
+const PLACE_ATTEMPTS: usize = 16;
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+//Runtime state of the spawn rule of a location in a room chunk
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Spawner {
+    //chunk the location is in
+    pub room: (u32, u32),
+    pub spawn: Spawn,
+    //tiles around the center of the room entities are placed within
+    pub radius: f32,
+    //spawns left, -1 is infinite
+    pub uses: i32,
+    //seconds until the next roll
+    pub timer: f32,
+    //auto spawners keep going once they have been triggered
+    pub active: bool,
+    //live entities this spawner made
+    pub spawned: Vec<EntityId>,
+}
+
+impl Spawner {
+    //one spawner for every location of the dungeon that spawns something
+    pub fn from_dungeon(dungeon: &Dungeon) -> Vec<Self> {
+        dungeon.locations().into_iter()
+            .filter_map(|(room, location)| location.spawn.as_ref().map(|spawn| Self {
+                room,
+                spawn: spawn.clone(),
+                radius: location.radius,
+                uses: spawn.uses,
+                timer: spawn.interval,
+                active: false,
+                spawned: Vec::new(),
+            }))
+            .collect()
+    }
+
+    //center of the room in chunks
+    fn center(&self) -> (f32, f32) {
+        (self.room.0 as f32 + 0.5, self.room.1 as f32 + 0.5)
+    }
+
+    fn is_triggered(&self, player: (f32, f32)) -> bool {
+        match self.spawn.trigger {
+            Trigger::OnEnter(_) => player.0.floor() as i64 == self.room.0 as i64 && player.1.floor() as i64 == self.room.1 as i64,
+            Trigger::Proximity(radius) => distance(self.center(), player) <= radius / CHUNK_SIZE,
+        }
+    }
+
+    //spawns the entity on a free walkable tile within the radius
+    fn place(&self, world: &mut World, navigation: &Navigation) -> Option<EntityId> {
+        let mut rng = rand::thread_rng();
+        let center = to_tile(self.center());
+        let radius = self.radius.max(0.0) as i32;
+        let mut spawned = None;
+        for _ in 0..PLACE_ATTEMPTS {
+            let tile = (center.0 + rng.gen_range(-radius..=radius), center.1 + rng.gen_range(-radius..=radius));
+            if !navigation.is_walkable(tile) {
+                continue;
+            }
+            let position = to_position(tile);
+            //the collider of the entity is needed to tell whether it fits, so it is moved around until it does
+            let id = *spawned.get_or_insert_with(|| self.spawn.entity.spawn(world, position));
+            let moved = if let Some(moved) = world.positions.get_mut(&id) {
+                moved.x = position.0;
+                moved.y = position.1;
+                moved.clone()
+            } else {
+                break;
+            };
+            if !world.is_blocked(id, &moved) {
+                return Some(id);
+            }
+        }
+        if let Some(id) = spawned {
+            world.despawn(id);
+        }
+        None
+    }
+}
+
+/**
+ * Rolls every spawner the player has triggered and removes spawned entities that are far away
+ * spawners: &mut [Spawner] - The spawners of the dungeon
+ * world: &mut World - The world entities are spawned into
+ * bus: &mut EventBus - Where the spawns are published
+ * navigation: &Navigation - The walkable tiles entities can be placed on
+ * player: (f32, f32) - Position of the player in chunks
+ * delta: f32 - Seconds since the last update
+ */
+pub fn update(spawners: &mut [Spawner], world: &mut World, bus: &mut EventBus, navigation: &Navigation, player: (f32, f32), delta: f32) {
+    let mut rng = rand::thread_rng();
+    for spawner in spawners.iter_mut() {
+        spawner.spawned.retain(|id| {
+            if !world.is_alive(*id) {
+                return false;
+            }
+            //friendly mobs stay, missions send the player away and back to them
+            if world.ais.get(id).map_or(false, |ai| ai.friendly || ai.leader.is_some()) {
+                return true;
+            }
+            let far = world.positions.get(id).map_or(true, |position| distance(position.get(), player) > DESPAWN_DISTANCE);
+            if far {
+                world.despawn(*id);
+            }
+            !far
+        });
+        let triggered = spawner.is_triggered(player);
+        spawner.active = triggered || (spawner.spawn.auto && spawner.active);
+        //nothing would survive being spawned out of reach of the player
+        if !spawner.active || spawner.uses == 0 || distance(spawner.center(), player) > DESPAWN_DISTANCE {
+            continue;
+        }
+        spawner.timer -= delta;
+        if spawner.timer > 0.0 {
+            continue;
+        }
+        spawner.timer += spawner.spawn.interval.max(delta);
+        if spawner.spawned.len() >= MAX_ALIVE || rng.gen::<f32>() >= spawner.spawn.chance {
+            continue;
+        }
+        if let Some(id) = spawner.place(world, navigation) {
+            debug!("Spawned {} in room {:?}", world.names.get(&id).map_or("Entity", |name| name.as_str()), spawner.room);
+            bus.publish(GameEvent::EntitySpawned { id, template: world.templates.get(&id).cloned() });
+            spawner.spawned.push(id);
+            if spawner.uses > 0 {
+                spawner.uses -= 1;
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+use serde::{Serialize, Deserialize};
+
+use crate::core::data::{Stats, race::Race, class::Class};
+
+use super::{entity::Inventory, components::Effects};
+
+//Health every entity has before vitality
+const BASE_HEALTH: f32 = 50.0;
+//Chunks per second before agility
+const BASE_SPEED: f32 = 0.3;
+//Attacks per second before agility
+const BASE_ATTACK_SPEED: f32 = 1.0;
+//Most of the time a hit can be critical
+const MAX_CRIT: f32 = 0.5;
+//Mana every entity gets back per second before intelligence
+const BASE_MANA_REGEN: f32 = 0.5;
+//Chunks a mob notices things within before agility and intelligence
+const BASE_PERCEPTION: f32 = 0.3;
+
+//Values the game uses, worked out from the effective stats
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Derived {
+    pub health: i32,
+    pub mana: i32,
+    //mana per second
+    pub mana_regen: f32,
+    //chunks per second
+    pub move_speed: f32,
+    //attacks per second
+    pub attack_speed: f32,
+    //multiplies how fast spells are cast
+    pub cast_speed: f32,
+    //chance from 0 to 1 that a hit is critical
+    pub crit: f32,
+    //multiplies the chance of every drop
+    pub loot: f32,
+    //fraction of incoming damage that is blocked
+    pub mitigation: f32,
+    //chunks within which hostile entities are noticed
+    pub perception: f32,
+}
+
+impl Derived {
+    //follows the descriptions of the stats in character.toml, mana multiplies the pool and regeneration
+    pub fn new(stats: &Stats, mana: f32) -> Self {
+        let stat = |value: i32| value.max(0) as f32;
+        Self {
+            health: (BASE_HEALTH + stat(stats.vit) * 5.0).round() as i32,
+            mana: (stat(stats.int) * 5.0 * mana).round() as i32,
+            mana_regen: (BASE_MANA_REGEN + stat(stats.int) / 20.0) * mana,
+            move_speed: BASE_SPEED * (1.0 + stat(stats.agi) / 100.0),
+            attack_speed: BASE_ATTACK_SPEED * (1.0 + stat(stats.agi) / 100.0),
+            cast_speed: 1.0 + stat(stats.int) / 100.0,
+            crit: (stat(stats.luk) / (stat(stats.luk) + 200.0)).min(MAX_CRIT),
+            loot: 1.0 + stat(stats.luk) / 100.0,
+            mitigation: stat(stats.def) / (stat(stats.def) + 100.0),
+            perception: BASE_PERCEPTION + (stat(stats.agi) + stat(stats.int)) / 200.0,
+        }
+    }
+}
+
+//Effective stats of an entity and where each part of them came from
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Sheet {
+    //name of each source and the stats it gives, in the order they were summed
+    pub sources: Vec<(String, Stats)>,
+    pub total: Stats,
+    pub derived: Derived,
+}
+
+impl Sheet {
+    /**
+     * Sums the stats of an entity from everything that gives it stats
+     * base: &Stats - The stats of the entity itself
+     * race: Option<&Race> - The race of a character
+     * class: Option<&Class> - The class of a character
+     * inventory: Option<&Inventory> - Equipped apparel and held items give their stats, unless they are broken
+     * effects: Option<&Effects> - Active effects give the stats of their source times their multiplier
+     */
+    pub fn new(base: &Stats, race: Option<&Race>, class: Option<&Class>, inventory: Option<&Inventory>, effects: Option<&Effects>) -> Self {
+        let mut sources = vec![("base".to_string(), base.clone())];
+        if let Some(race) = race {
+            sources.push((race.name.clone(), race.stats.clone()));
+        }
+        if let Some(class) = class {
+            sources.push((class.name.clone(), class.stats.clone()));
+        }
+        if let Some(inventory) = inventory {
+            //the inventory is a hashmap, sorting keeps the breakdown from jumping around
+            let mut equipped = inventory.apparel.values()
+                .chain(inventory.holding.values())
+                .filter_map(|slot| slot.item.as_ref())
+                //broken equipment gives nothing until it is repaired
+                .filter(|item| !item.is_broken())
+                .map(|item| (item.display_name(), item.total_stats()))
+                .collect::<Vec<_>>();
+            equipped.sort_by(|a, b| a.0.cmp(&b.0));
+            sources.extend(equipped);
+        }
+        if let Some(effects) = effects {
+            for active in &effects.active {
+                sources.push((active.effect.name.clone(), active.stats.scaled(active.multiplier)));
+            }
+        }
+        let mut total = Stats::default();
+        for (_, stats) in &sources {
+            total += stats;
+        }
+        let derived = Derived::new(&total, class.map_or(1.0, |class| class.mana));
+        Self {
+            sources,
+            total,
+            derived,
+        }
+    }
+}
\ No newline at end of file
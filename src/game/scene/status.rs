@@ -0,0 +1,279 @@
+use rand::Rng;
+
+use crate::core::{data::{Stats, affinity::AffinityTable, effect::{Effect, Trigger, Stacking}, item::Kind}, logger::debug};
+
+use super::{world::{World, EntityId}, components::ActiveEffect, combat::{self, Hit, is_hostile}, entity::{Item, SlotRef}};
+
+//Seconds in a tick, which is what effect intervals are counted in
+const TICK: f32 = 1.0 / 20.0;
+//Chunks around the holder that proximity effects reach
+const PROXIMITY_RADIUS: f32 = 0.3;
+
+fn interval(effect: &Effect) -> f32 {
+    effect.interval.max(1) as f32 * TICK
+}
+
+/**
+ * Rolls the chance of an effect and attaches it to an entity
+ * world: &mut World - The world the target is in
+ * target: EntityId - The entity the effect is applied to
+ * effect: &Effect - The effect to apply
+ * stats: &Stats - Stats given while the effect is applied, usually those of the item applying it
+ * source: Option<EntityId> - The entity applying the effect
+ * uses: Option<i32> - Ticks an auto effect runs for, None runs for the duration instead
+ * returns: bool - Whether the effect was applied
+ */
+pub fn apply(world: &mut World, target: EntityId, effect: &Effect, stats: &Stats, source: Option<EntityId>, uses: Option<i32>) -> bool {
+    let effects = if let Some(effects) = world.effects.get_mut(&target) {
+        effects
+    } else {
+        return false;
+    };
+    let mut rng = rand::thread_rng();
+    if rng.gen::<f32>() >= effect.chance {
+        return false;
+    }
+    let multiplier = rng.gen_range(effect.min.min(effect.max)..=effect.max.max(effect.min));
+    let existing = effects.active.iter_mut().find(|active| active.effect.id == effect.id);
+    match (&effect.stacking, existing) {
+        (Stacking::Ignore, Some(_)) => false,
+        (Stacking::Refresh, Some(active)) => {
+            active.remaining = effect.duration as f32;
+            active.multiplier = multiplier;
+            active.stats = stats.clone();
+            active.uses = uses;
+            active.source = source;
+            true
+        },
+        _ => {
+            effects.active.push(ActiveEffect {
+                effect: effect.clone(),
+                stats: stats.clone(),
+                multiplier,
+                remaining: effect.duration as f32,
+                //ticks right away, so effects without a duration still happen once
+                timer: interval(effect),
+                uses,
+                source,
+                heal_carry: 0.0,
+                damage_carry: 0.0,
+            });
+            true
+        }
+    }
+}
+
+//uses an item up once, returns whether the item is used up
+fn wear(item: &mut Item) -> bool {
+    //equipment loses durability in combat instead, and is never used up
+    if item.uses < 0 || item.has_durability() {
+        //-1 is infinite
+        return false;
+    }
+    //auto items keep going on their own until all of their uses are gone
+    item.uses = if item.auto { 0 } else { (item.uses - 1).max(0) };
+    item.uses == 0
+}
+
+//applies the effect of the item in a slot and wears the item down
+fn fire(world: &mut World, holder: EntityId, slot: &SlotRef, target: EntityId) -> bool {
+    let item = if let Some(item) = world.inventories.get(&holder).and_then(|inventory| inventory.get(slot)).and_then(|slot| slot.item.clone()) {
+        item
+    } else {
+        return false;
+    };
+    let effect = if let Some(effect) = item.effect.as_ref() {
+        effect
+    } else {
+        return false;
+    };
+    let uses = if item.auto && item.uses > 0 { Some(item.uses) } else { None };
+    if !apply(world, target, effect, &item.stats, Some(holder), uses) {
+        return false;
+    }
+    if let Some(slot) = world.inventories.get_mut(&holder).and_then(|inventory| inventory.get_mut(slot)) {
+        let used_up = slot.item.as_mut().map_or(false, wear);
+        if used_up {
+            debug!("{} was used up", item.name);
+            slot.item = None;
+            slot.amount = 0;
+        }
+    }
+    true
+}
+
+//equipment slots holding an item whose effect has the trigger
+fn equipped(world: &World, holder: EntityId, trigger: &Trigger) -> Vec<SlotRef> {
+    let inventory = if let Some(inventory) = world.inventories.get(&holder) {
+        inventory
+    } else {
+        return Vec::new();
+    };
+    let mut slots = inventory.apparel.values().map(|slot| (true, slot))
+        .chain(inventory.holding.values().map(|slot| (false, slot)))
+        .filter(|(_, slot)| {
+            slot.item.as_ref()
+                .filter(|item| !item.is_broken())
+                .and_then(|item| item.effect.as_ref())
+                .map_or(false, |effect| std::mem::discriminant(&effect.trigger) == std::mem::discriminant(trigger))
+        })
+        .map(|(apparel, slot)| (apparel, slot.name.clone()))
+        .collect::<Vec<_>>();
+    slots.sort();
+    slots.into_iter()
+        .map(|(apparel, name)| if apparel { SlotRef::Apparel(name) } else { SlotRef::Holding(name) })
+        .collect()
+}
+
+/**
+ * Uses a consumable, applying its effect to the holder
+ * world: &mut World - The world the holder is in
+ * holder: EntityId - The entity using the item
+ * slot: &SlotRef - The slot the item is in, consumables are used from the bag as well as the hands
+ * returns: bool - Whether the item was used, false while it is cooling down
+ */
+pub fn use_item(world: &mut World, holder: EntityId, slot: &SlotRef) -> bool {
+    let (id, cooldown) = if let Some(item) = world.inventories.get(&holder).and_then(|inventory| inventory.get(slot)).and_then(|slot| slot.item.as_ref()) {
+        if item.kind != Kind::Consumable || !item.effect.as_ref().map_or(false, |effect| matches!(effect.trigger, Trigger::Use)) {
+            return false;
+        }
+        (item.id.clone(), item.cooldown)
+    } else {
+        return false;
+    };
+    //consumables of the same kind share a cooldown
+    let ready = world.effects.get(&holder).map_or(false, |effects| effects.item_cooldowns.get(&id).map_or(true, |left| *left <= 0.0));
+    if !ready || !fire(world, holder, slot, holder) {
+        return false;
+    }
+    if let Some(effects) = world.effects.get_mut(&holder) {
+        effects.item_cooldowns.insert(id, cooldown);
+    }
+    true
+}
+
+/**
+ * Fires the effects of the equipment of an entity that have a trigger
+ * world: &mut World - The world the holder is in
+ * holder: EntityId - The entity wearing or holding the equipment
+ * trigger: Trigger - Use and Hit apply to the holder, Contact and Proximity apply to the other entity
+ * other: Option<EntityId> - The entity that was hit or is close by
+ */
+pub fn trigger(world: &mut World, holder: EntityId, trigger: Trigger, other: Option<EntityId>) {
+    let target = match (&trigger, other) {
+        (Trigger::Use, _) | (Trigger::Hit, _) => holder,
+        (Trigger::Contact, Some(other)) | (Trigger::Proximity, Some(other)) => other,
+        _ => return,
+    };
+    for slot in equipped(world, holder, &trigger) {
+        fire(world, holder, &slot, target);
+    }
+}
+
+//fires proximity equipment at hostile entities in range, each item waits for its interval between triggers
+fn proximity(world: &mut World) {
+    let holders = world.inventories.keys().cloned().collect::<Vec<_>>();
+    for holder in holders {
+        let origin = if let Some(position) = world.positions.get(&holder) {
+            position.get()
+        } else {
+            continue;
+        };
+        for slot in equipped(world, holder, &Trigger::Proximity) {
+            let (name, wait) = if let Some(item) = world.inventories.get(&holder).and_then(|inventory| inventory.get(&slot)).and_then(|slot| slot.item.as_ref()) {
+                (item.name.clone(), item.effect.as_ref().map_or(TICK, interval))
+            } else {
+                continue;
+            };
+            let ready = world.effects.get(&holder).map_or(false, |effects| effects.proximity_cooldowns.get(&name).map_or(true, |cooldown| *cooldown <= 0.0));
+            if !ready {
+                continue;
+            }
+            let targets = world.positions.iter()
+                .filter(|(target, position)| {
+                    let (x, y) = (position.x - origin.0, position.y - origin.1);
+                    is_hostile(world, holder, **target) && (x * x + y * y).sqrt() <= PROXIMITY_RADIUS
+                })
+                .map(|(target, _)| *target)
+                .collect::<Vec<_>>();
+            for target in targets {
+                fire(world, holder, &slot, target);
+            }
+            if let Some(effects) = world.effects.get_mut(&holder) {
+                effects.proximity_cooldowns.insert(name, wait);
+            }
+        }
+    }
+}
+
+/**
+ * Ticks every applied effect at its interval and removes the ones that ran out
+ * world: &mut World - The world the effects are applied in
+ * affinities: &AffinityTable - How the affinity of whoever applied an effect changes its damage to the holder
+ * delta: f32 - Seconds since the last update
+ * returns: Vec<Hit> - The damage the effects dealt
+ */
+pub fn update(world: &mut World, affinities: &AffinityTable, delta: f32) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    //cooldowns run out on everyone, whether or not they hold anything
+    for effects in world.effects.values_mut() {
+        for cooldowns in [&mut effects.item_cooldowns, &mut effects.proximity_cooldowns] {
+            cooldowns.values_mut().for_each(|cooldown| *cooldown -= delta);
+            cooldowns.retain(|_, cooldown| *cooldown > 0.0);
+        }
+    }
+    proximity(world);
+    //taken out of the world while ticking, so damage can look at the affinities and defense of the holder
+    let mut all = std::mem::take(&mut world.effects);
+    for (id, effects) in all.iter_mut() {
+        let mut heal = 0;
+        let mut dealt = Vec::new();
+        for active in effects.active.iter_mut() {
+            let wait = interval(&active.effect);
+            active.timer += delta;
+            while active.timer >= wait {
+                active.timer -= wait;
+                if active.uses.map_or(false, |uses| uses <= 0) {
+                    break;
+                }
+                active.heal_carry += active.effect.heal * active.multiplier;
+                //damage over time hurts like a hit from whoever applied it, healing is not an attack
+                if active.effect.damage > 0.0 {
+                    active.damage_carry += combat::harm(world, affinities, active.source, *id, active.effect.damage * active.multiplier);
+                }
+                if let Some(uses) = active.uses.as_mut() {
+                    *uses -= 1;
+                }
+            }
+            active.remaining -= delta;
+            //only whole points land
+            let healed = active.heal_carry.trunc();
+            active.heal_carry -= healed;
+            heal += healed as i32;
+            let damage = active.damage_carry.trunc();
+            active.damage_carry -= damage;
+            if damage > 0.0 {
+                dealt.push((active.source, damage as i32));
+            }
+        }
+        //auto effects last until their uses are gone, the rest last for their duration
+        effects.active.retain(|active| match active.uses {
+            Some(uses) => uses > 0,
+            None => active.remaining > 0.0,
+        });
+        if let Some(health) = world.healths.get_mut(id) {
+            health.current = (health.current + heal).min(health.max);
+            health.current -= dealt.iter().map(|(_, damage)| damage).sum::<i32>();
+        }
+        //whoever applied the damage gets the credit when the holder dies of it
+        if let Some((source, _)) = dealt.iter().rev().find(|(source, _)| source.map_or(false, |source| source != *id)) {
+            if let Some(combatant) = world.combatants.get_mut(id) {
+                combatant.attacker = *source;
+            }
+        }
+        //effects nobody applied are dealt by the holder to itself
+        hits.extend(dealt.into_iter().map(|(source, damage)| Hit { attacker: source.unwrap_or(*id), target: *id, damage, critical: false }));
+    }
+    world.effects = all;
+    hits
+}
\ No newline at end of file
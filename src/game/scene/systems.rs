@@ -0,0 +1,98 @@
+use std::cmp::Ordering;
+
+use ggez::glam;
+
+use crate::{core::toml_loader::{TextureMap, Settings}, game::batch::SpriteBatch};
+
+use super::{world::{World, EntityId}, camera::Camera, stats::Sheet};
+
+//Systems run over every entity in the world that has the components they need
+
+//recalculates effective stats, health and mana follow the new maximum
+pub fn stats(world: &mut World) {
+    for (id, base) in world.stats.iter() {
+        let sheet = Sheet::new(
+            base,
+            world.races.get(id),
+            world.classes.get(id),
+            world.inventories.get(id),
+            world.effects.get(id)
+        );
+        if let Some(health) = world.healths.get_mut(id) {
+            health.set_max(sheet.derived.health);
+        }
+        if let Some(mana) = world.manas.get_mut(id) {
+            mana.set_max(sheet.derived.mana);
+        }
+        world.sheets.insert(*id, sheet);
+    }
+}
+
+//builds missing animators and keeps the drawn equipment in sync with inventories
+pub fn dress(world: &mut World, texture_map: &TextureMap) {
+    for (id, sprite) in world.sprites.iter_mut() {
+        if sprite.animator.is_none() {
+            sprite.skin(texture_map);
+        }
+        if let Some(inventory) = world.inventories.get(id) {
+            sprite.outfit.dress(texture_map, inventory);
+        }
+    }
+}
+
+//plays the clip of the motion and direction of every sprite, delta is in seconds
+pub fn animate(world: &mut World, delta: f32) {
+    for (id, sprite) in world.sprites.iter_mut() {
+        let direction = if let Some(position) = world.positions.get(id) {
+            position.direction.clone()
+        } else {
+            continue;
+        };
+        sprite.outfit.animate(sprite.motion.clone(), direction.clone(), delta);
+        if let Some(animator) = sprite.animator.as_mut() {
+            animator.set(sprite.motion.clone(), direction);
+            animator.update(delta);
+        }
+    }
+}
+
+/**
+ * Queues every sprite that is on screen, entities further down are drawn on top
+ * world: &World - The entities to draw
+ * order: &mut Vec<EntityId> - Reused between frames so sorting does not allocate
+ * batch: &mut SpriteBatch - The batch to queue the frames in
+ * texture_map: &TextureMap - Source of the layering
+ * camera: &Camera - Where the entities are drawn on screen
+ * settings: &Settings - Size of the screen
+ */
+pub fn draw(world: &World, order: &mut Vec<EntityId>, batch: &mut SpriteBatch, texture_map: &TextureMap, camera: &Camera, settings: &Settings) {
+    let ((start_x, start_y), (end_x, end_y)) = camera.visible_chunks(settings, 1);
+    order.clear();
+    order.extend(world.sprites.keys().filter(|id| {
+        if let Some(position) = world.positions.get(id) {
+            position.x >= start_x as f32 && position.x <= end_x as f32 && position.y >= start_y as f32 && position.y <= end_y as f32
+        } else {
+            false
+        }
+    }));
+    order.sort_by(|a, b| {
+        let (a, b) = (&world.positions[a], &world.positions[b]);
+        a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal)
+    });
+    let scale = camera.scale(settings);
+    for id in order.iter() {
+        let (sprite, position) = (&world.sprites[id], &world.positions[id]);
+        let frame = sprite.animator.as_ref().and_then(|animator| animator.get_frame());
+        let (w, h) = frame.map(|frame| (frame.w, frame.h)).unwrap_or_default();
+        //positions are the center of the entity
+        let dest = camera.world_to_screen(position.get(), settings);
+        sprite.outfit.draw(
+            batch,
+            frame,
+            &position.direction,
+            texture_map,
+            glam::Vec2::new(dest.x - w * scale / 2.0, dest.y - h * scale / 2.0),
+            scale
+        );
+    }
+}
\ No newline at end of file
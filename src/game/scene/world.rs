@@ -0,0 +1,116 @@
+use std::collections::{HashMap, BTreeSet};
+
+use serde::{Serialize, Deserialize};
+
+use crate::core::data::{Stats, Affinity, race::Race, class::Class};
+
+use super::{components::{Position, Sprite, Health, Ai, Effects, Collider, Pickup, Level, Shield, Mana, Flight}, entity::{Inventory, Drops}, stats::Sheet, combat::Combatant, magic::{Spell, Casting}};
+
+pub use crate::core::EntityId;
+
+//Component store for every live entity in a scene
+// an entity is only an id, what it is depends on the components it has
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct World {
+    next: EntityId,
+    //every live id, tells whether an entity is still alive and counts them, ids come from next so they are never reused
+    entities: BTreeSet<EntityId>,
+    pub names: HashMap<EntityId, String>,
+    //id of the data the entity was spawned from
+    pub templates: HashMap<EntityId, String>,
+    pub positions: HashMap<EntityId, Position>,
+    pub sprites: HashMap<EntityId, Sprite>,
+    //stats of the entity itself, before race, class, equipment and effects
+    pub stats: HashMap<EntityId, Stats>,
+    pub races: HashMap<EntityId, Race>,
+    pub classes: HashMap<EntityId, Class>,
+    //effective stats, recalculated every tick
+    #[serde(skip)]
+    pub sheets: HashMap<EntityId, Sheet>,
+    pub affinities: HashMap<EntityId, Affinity>,
+    pub healths: HashMap<EntityId, Health>,
+    pub manas: HashMap<EntityId, Mana>,
+    pub ais: HashMap<EntityId, Ai>,
+    pub inventories: HashMap<EntityId, Inventory>,
+    pub effects: HashMap<EntityId, Effects>,
+    pub colliders: HashMap<EntityId, Collider>,
+    pub combatants: HashMap<EntityId, Combatant>,
+    //what an entity leaves behind when it dies
+    pub drops: HashMap<EntityId, Vec<Drops>>,
+    pub pickups: HashMap<EntityId, Pickup>,
+    pub levels: HashMap<EntityId, Level>,
+    //runes an entity casts with the cast key
+    pub spells: HashMap<EntityId, Spell>,
+    pub shields: HashMap<EntityId, Shield>,
+    //spells being cast that have not gone off yet
+    pub castings: HashMap<EntityId, Casting>,
+    pub projectiles: HashMap<EntityId, Flight>,
+}
+
+impl World {
+    //creates an entity without any components
+    pub fn spawn(&mut self) -> EntityId {
+        let id = self.next;
+        self.next += 1;
+        self.entities.insert(id);
+        id
+    }
+
+    //removes an entity and all of its components
+    pub fn despawn(&mut self, id: EntityId) {
+        self.entities.remove(&id);
+        self.names.remove(&id);
+        self.templates.remove(&id);
+        self.positions.remove(&id);
+        self.sprites.remove(&id);
+        self.stats.remove(&id);
+        self.races.remove(&id);
+        self.classes.remove(&id);
+        self.sheets.remove(&id);
+        self.affinities.remove(&id);
+        self.healths.remove(&id);
+        self.manas.remove(&id);
+        self.ais.remove(&id);
+        self.inventories.remove(&id);
+        self.effects.remove(&id);
+        self.colliders.remove(&id);
+        self.combatants.remove(&id);
+        self.drops.remove(&id);
+        self.pickups.remove(&id);
+        self.levels.remove(&id);
+        self.spells.remove(&id);
+        self.shields.remove(&id);
+        self.castings.remove(&id);
+        self.projectiles.remove(&id);
+    }
+
+    //whether a solid entity would overlap another solid entity at a position
+    pub fn is_blocked(&self, id: EntityId, position: &Position) -> bool {
+        let collider = if let Some(collider) = self.colliders.get(&id) {
+            collider
+        } else {
+            return false;
+        };
+        if !collider.solid {
+            return false;
+        }
+        self.colliders.iter().any(|(other, other_collider)| {
+            if *other == id || !other_collider.solid {
+                return false;
+            }
+            if let Some(other_position) = self.positions.get(other) {
+                collider.overlaps(position, other_collider, other_position)
+            } else {
+                false
+            }
+        })
+    }
+
+    pub fn is_alive(&self, id: EntityId) -> bool {
+        self.entities.contains(&id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.entities.len()
+    }
+}
\ No newline at end of file
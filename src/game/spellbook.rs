@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use egui::{RichText, Color32};
+
+use crate::core::data::rune::Rune;
+
+use super::scene::magic::Spell;
+
+//Highest number a rune can be given
+const MAX_VALUE: i32 = 99;
+
+//Changes the player made to their spell
+#[derive(Debug, Clone)]
+pub enum Edit {
+    //adds a rune by id with the number it takes
+    Push(String, Option<i32>),
+    Pop,
+    Clear,
+}
+
+//Spellbook toggled with the spellbook key, runes are chained into the spell cast with the cast key
+#[derive(Debug)]
+pub struct Spellbook {
+    pub visible: bool,
+    //number given to the next rune that takes one
+    value: i32,
+}
+
+impl Default for Spellbook {
+    fn default() -> Self {
+        Spellbook {
+            visible: false,
+            value: 1,
+        }
+    }
+}
+
+impl Spellbook {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /**
+     * Shows every rune and the spell they are chained into
+     * gui_ctx: &egui::Context - The context to show the window in
+     * runes: &HashMap<String, Rune> - Every rune, keyed by id
+     * spell: &Spell - The spell of the player
+     * text_size: f32 - The size of the text
+     * returns: Option<Edit> - The change the player made to the spell
+     */
+    pub fn show(&mut self, gui_ctx: &egui::Context, runes: &HashMap<String, Rune>, spell: &Spell, text_size: f32) -> Option<Edit> {
+        if !self.visible {
+            return None;
+        }
+        let mut edit = None;
+        let text = |text: String| RichText::new(text).size(text_size);
+        //the runes are a hashmap, sorting keeps the buttons from jumping around
+        let mut sorted = runes.values().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.id.cmp(&b.id));
+        egui::Window::new(RichText::new("Spellbook").size(text_size).color(Color32::DARK_GRAY))
+            .id(egui::Id::new("spellbook"))
+            .resizable(false)
+            .collapsible(false)
+            .show(gui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for rune in &sorted {
+                        let label = if rune.takes_value() { format!("{} (n)", rune.name) } else { rune.name.clone() };
+                        let response = ui.button(text(label)).on_hover_ui(|ui| {
+                            ui.label(text(rune.description.clone()));
+                            let cost = if rune.takes_value() { "n".to_string() } else { rune.drain.to_string() };
+                            ui.label(text(format!("drain: {}", cost)).color(Color32::LIGHT_BLUE));
+                            if rune.render_attribute {
+                                ui.label(text("visible".to_string()).color(Color32::GRAY));
+                            }
+                        });
+                        if response.clicked() {
+                            let value = if rune.takes_value() { Some(self.value) } else { None };
+                            edit = Some(Edit::Push(rune.id.clone(), value));
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(text("n".to_string()));
+                    ui.add(egui::DragValue::new(&mut self.value).clamp_range(1..=MAX_VALUE));
+                });
+                ui.separator();
+                //the spell reads left to right in the order it is cast
+                let chain = spell.glyphs.iter()
+                    .map(|glyph| {
+                        let name = runes.get(&glyph.rune).map_or(glyph.rune.clone(), |rune| rune.name.clone());
+                        glyph.value.map_or(name.clone(), |value| format!("{} {}", name, value))
+                    })
+                    .collect::<Vec<_>>();
+                if chain.is_empty() {
+                    ui.label(text("no runes".to_string()).color(Color32::GRAY));
+                } else {
+                    ui.label(text(chain.join(" > ")));
+                }
+                ui.label(text(format!("mana: {}", spell.cost(runes))).color(Color32::LIGHT_BLUE));
+                ui.horizontal(|ui| {
+                    if ui.button(text("undo".to_string())).clicked() {
+                        edit = Some(Edit::Pop);
+                    }
+                    if ui.button(text("clear".to_string())).clicked() {
+                        edit = Some(Edit::Clear);
+                    }
+                });
+            });
+        edit
+    }
+}
\ No newline at end of file
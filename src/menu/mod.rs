@@ -82,7 +82,7 @@ impl Menu {
             panic!("Integrity check failed: {}", err);
         }
         configuration.settings.set_render_scale();
-        configuration.map_textures();
+        configuration.map_textures(ctx);
         #[cfg(debug_assertions)]
         let image_path = current_dir().unwrap().join("core").join("assets").join("images").join("background.png");
         #[cfg(not(debug_assertions))]
@@ -142,11 +142,13 @@ impl EventHandler for Menu {
                             "Play" => {
                                 debug!("Play");
                                 //within the context of *this* scene, the configs can be changed
-                                let game = Game::new(ctx, self.configuration.clone());
+                                let game = Game::new(ctx, self.configuration.clone(), false);
                                 self.event = Some(Box::new(game));
                             },
                             "Load" => {
                                 debug!("Load");
+                                let game = Game::new(ctx, self.configuration.clone(), true);
+                                self.event = Some(Box::new(game));
                             },
                             "Settings" => {
                                 debug!("Settings");
@@ -172,6 +174,12 @@ impl EventHandler for Menu {
         self.gui.update(ctx);
         Ok(())
     }
+    fn mouse_wheel_event(&mut self, _ctx: &mut ggez::Context, x: f32, y: f32) -> ggez::GameResult {
+        if let Some(event) = self.event.as_mut() {
+            event.mouse_wheel(x, y);
+        }
+        Ok(())
+    }
     fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
         let mut canvas = graphics::Canvas::from_frame(ctx, BG_COLOR);
         if let Some(val) = self.event.as_mut() {